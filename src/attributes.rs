@@ -0,0 +1,89 @@
+use ffi::*;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct Vector3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vector3 {
+    pub fn new(x: f32, y: f32, z: f32) -> Vector3 {
+        Vector3 { x, y, z }
+    }
+}
+
+impl From<FMOD_VECTOR> for Vector3 {
+    fn from(v: FMOD_VECTOR) -> Vector3 {
+        Vector3::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<Vector3> for FMOD_VECTOR {
+    fn from(v: Vector3) -> FMOD_VECTOR {
+        FMOD_VECTOR {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+/// The position, velocity and orientation of an event or listener in 3D
+/// space. FMOD uses a left-handed coordinate system unless the system was
+/// built with `SystemBuilder::right_handed_3d`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct Attributes3D {
+    pub position: Vector3,
+    /// Velocity in units per second.
+    pub velocity: Vector3,
+    /// Must be of unit length and perpendicular to `up`.
+    pub forward: Vector3,
+    /// Must be of unit length and perpendicular to `forward`.
+    pub up: Vector3,
+}
+
+impl Attributes3D {
+    /// Attributes at `position` with no velocity, facing along +Z with +Y up.
+    pub fn at(position: Vector3) -> Attributes3D {
+        Attributes3D {
+            position,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for Attributes3D {
+    fn default() -> Attributes3D {
+        Attributes3D {
+            position: Vector3::default(),
+            velocity: Vector3::default(),
+            forward: Vector3::new(0.0, 0.0, 1.0),
+            up: Vector3::new(0.0, 1.0, 0.0),
+        }
+    }
+}
+
+impl From<FMOD_3D_ATTRIBUTES> for Attributes3D {
+    fn from(attr: FMOD_3D_ATTRIBUTES) -> Attributes3D {
+        Attributes3D {
+            position: attr.position.into(),
+            velocity: attr.velocity.into(),
+            forward: attr.forward.into(),
+            up: attr.up.into(),
+        }
+    }
+}
+
+impl From<Attributes3D> for FMOD_3D_ATTRIBUTES {
+    fn from(attr: Attributes3D) -> FMOD_3D_ATTRIBUTES {
+        FMOD_3D_ATTRIBUTES {
+            position: attr.position.into(),
+            velocity: attr.velocity.into(),
+            forward: attr.forward.into(),
+            up: attr.up.into(),
+        }
+    }
+}
@@ -1,21 +1,67 @@
 use std::{fmt, mem, ptr};
+use std::marker::PhantomData;
+use std::ops::{BitOr, BitOrAssign, Deref};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
 
 use ffi::*;
 use error::*;
+use bus::Bus;
 use event::EventDescription;
 use guid::Guid;
+use string::get_string;
+use system::System;
+use vca::Vca;
 
 #[derive(Copy, Clone)]
 #[repr(C)]
-pub struct Bank {
+pub struct Bank<'sys> {
     pub(crate) ptr: *mut FMOD_STUDIO_BANK,
+    system: PhantomData<&'sys System>,
 }
 
-unsafe impl Send for Bank {}
-unsafe impl Sync for Bank {}
+unsafe impl<'sys> Send for Bank<'sys> {}
+unsafe impl<'sys> Sync for Bank<'sys> {}
 
-impl Bank {
-    // TODO: Buses
+impl<'sys> Bank<'sys> {
+    pub(crate) fn from_ptr(ptr: *mut FMOD_STUDIO_BANK) -> Self {
+        Bank {
+            ptr,
+            system: PhantomData,
+        }
+    }
+
+    /// Whether the bank is still loaded. Methods on an unloaded bank return
+    /// `FmodError::InvalidHandle`.
+    pub fn is_valid(&self) -> bool {
+        unsafe { FMOD_Studio_Bank_IsValid(self.ptr) != 0 }
+    }
+
+    pub fn get_bus_count(&self) -> Result<i32> {
+        unsafe {
+            let mut count = 0;
+            FMOD_Studio_Bank_GetBusCount(self.ptr, &mut count).to_err()?;
+            Ok(count)
+        }
+    }
+
+    pub fn get_buses(&self) -> Result<Vec<Bus<'sys>>> {
+        unsafe {
+            let mut count = 0;
+            FMOD_Studio_Bank_GetBusCount(self.ptr, &mut count).to_err()?;
+
+            let mut buses = Vec::with_capacity(count as usize);
+            FMOD_Studio_Bank_GetBusList(
+                self.ptr,
+                buses.as_mut_ptr() as *mut _,
+                buses.capacity() as i32,
+                &mut count,
+            ).to_err()?;
+            buses.set_len(count as usize);
+
+            Ok(buses)
+        }
+    }
 
     pub fn get_event_count(&self) -> Result<i32> {
         unsafe {
@@ -25,7 +71,7 @@ impl Bank {
         }
     }
 
-    pub fn get_events(&self) -> Result<Vec<EventDescription>> {
+    pub fn get_events(&self) -> Result<Vec<EventDescription<'sys>>> {
         unsafe {
             let mut count = 0;
             FMOD_Studio_Bank_GetEventCount(self.ptr, &mut count).to_err()?;
@@ -55,7 +101,7 @@ impl Bank {
 
     pub fn get_loading_state(&self) -> Result<LoadingState> {
         unsafe {
-            let mut state = mem::uninitialized();
+            let mut state = FMOD_STUDIO_LOADING_STATE::FMOD_STUDIO_LOADING_STATE_UNLOADED;
 
             FMOD_Studio_Bank_GetLoadingState(self.ptr, &mut state).to_err()?;
 
@@ -80,7 +126,7 @@ impl Bank {
 
     pub fn get_sample_loading_state(&self) -> Result<LoadingState> {
         unsafe {
-            let mut state = mem::uninitialized();
+            let mut state = FMOD_STUDIO_LOADING_STATE::FMOD_STUDIO_LOADING_STATE_UNLOADED;
 
             FMOD_Studio_Bank_GetSampleLoadingState(self.ptr, &mut state).to_err()?;
 
@@ -104,24 +150,74 @@ impl Bank {
     }
 
     pub fn get_path(&self) -> Result<String> {
+        get_string(|path, size, retrieved| unsafe {
+            FMOD_Studio_Bank_GetPath(self.ptr, path, size, retrieved)
+        })
+    }
+
+    pub fn get_string_count(&self) -> Result<i32> {
         unsafe {
-            let mut size = 0;
-            FMOD_Studio_Bank_GetPath(self.ptr, ptr::null_mut(), 0, &mut size).to_err()?;
+            let mut count = 0;
+            FMOD_Studio_Bank_GetStringCount(self.ptr, &mut count).to_err()?;
+            Ok(count)
+        }
+    }
 
-            let mut data = vec![0u8; size as usize];
-            FMOD_Studio_Bank_GetPath(self.ptr, data.as_mut_ptr() as *mut _, size, ptr::null_mut())
-                .to_err()?;
+    /// The GUID and path of an entry in the bank's string table. Only
+    /// master strings banks contain strings.
+    pub fn get_string_info(&self, index: i32) -> Result<(Guid, String)> {
+        unsafe {
+            let mut id = mem::zeroed();
+            let path = get_string(|path, size, retrieved| {
+                FMOD_Studio_Bank_GetStringInfo(self.ptr, index, &mut id, path, size, retrieved)
+            })?;
 
-            Ok(String::from_utf8_lossy(&data).into_owned())
+            Ok((Guid { inner: id }, path))
         }
     }
 
-    // TODO: VCAs
+    pub fn strings(&self) -> Result<BankStrings<'sys>> {
+        Ok(BankStrings {
+            bank: *self,
+            index: 0,
+            count: self.get_string_count()?,
+        })
+    }
+
+    pub fn get_vca_count(&self) -> Result<i32> {
+        unsafe {
+            let mut count = 0;
+            FMOD_Studio_Bank_GetVCACount(self.ptr, &mut count).to_err()?;
+            Ok(count)
+        }
+    }
+
+    pub fn get_vcas(&self) -> Result<Vec<Vca<'sys>>> {
+        unsafe {
+            let mut count = 0;
+            FMOD_Studio_Bank_GetVCACount(self.ptr, &mut count).to_err()?;
+
+            let mut vcas = Vec::with_capacity(count as usize);
+            FMOD_Studio_Bank_GetVCAList(
+                self.ptr,
+                vcas.as_mut_ptr() as *mut _,
+                vcas.capacity() as i32,
+                &mut count,
+            ).to_err()?;
+            vcas.set_len(count as usize);
+
+            Ok(vcas)
+        }
+    }
 
     pub fn load_sample_data(&self) -> Result<()> {
         unsafe { FMOD_Studio_Bank_LoadSampleData(self.ptr).to_err() }
     }
 
+    pub fn unload_sample_data(&self) -> Result<()> {
+        unsafe { FMOD_Studio_Bank_UnloadSampleData(self.ptr).to_err() }
+    }
+
     pub fn unload(&self) -> Result<()> {
         unsafe { FMOD_Studio_Bank_Unload(self.ptr).to_err() }
     }
@@ -131,7 +227,7 @@ impl Bank {
     }
 }
 
-impl fmt::Debug for Bank {
+impl<'sys> fmt::Debug for Bank<'sys> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.debug_struct("Bank")
             .field("path", &self.get_path())
@@ -140,6 +236,226 @@ impl fmt::Debug for Bank {
     }
 }
 
+/// Iterates over the GUIDs and paths in the string table of a bank, created
+/// by `Bank::strings`.
+pub struct BankStrings<'sys> {
+    bank: Bank<'sys>,
+    index: i32,
+    count: i32,
+}
+
+impl<'sys> Iterator for BankStrings<'sys> {
+    type Item = Result<(Guid, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+
+        let string = self.bank.get_string_info(self.index);
+        self.index += 1;
+        Some(string)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.count - self.index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'sys> ExactSizeIterator for BankStrings<'sys> {}
+
+/// A shared bank loaded with `System::load_bank_file_shared`. Clones refer to
+/// the same bank, which is unloaded when the last of them is dropped unless
+/// `System::unload_all` has unloaded it already.
+#[derive(Clone)]
+pub struct BankHandle<'sys> {
+    bank: Bank<'sys>,
+    shared: Arc<SharedBank>,
+}
+
+impl<'sys> BankHandle<'sys> {
+    pub(crate) fn new(shared: Arc<SharedBank>) -> Self {
+        BankHandle {
+            bank: Bank::from_ptr(shared.ptr),
+            shared,
+        }
+    }
+
+    pub fn bank(&self) -> Bank<'sys> {
+        self.bank
+    }
+
+    /// The file the bank was loaded from.
+    pub fn file(&self) -> &str {
+        &self.shared.file
+    }
+
+    /// The number of handles currently sharing the bank.
+    pub fn handle_count(&self) -> usize {
+        Arc::strong_count(&self.shared)
+    }
+}
+
+impl<'sys> Deref for BankHandle<'sys> {
+    type Target = Bank<'sys>;
+
+    fn deref(&self) -> &Bank<'sys> {
+        &self.bank
+    }
+}
+
+impl<'sys> fmt::Debug for BankHandle<'sys> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("BankHandle")
+            .field("file", &self.shared.file)
+            .field("handle_count", &self.handle_count())
+            .field("bank", &self.bank)
+            .finish()
+    }
+}
+
+/// The banks shared through `BankHandle`, keyed by the file they were loaded
+/// from. Every `SharedBank` keeps the registry alive so that it can remove
+/// itself when dropped.
+pub(crate) struct BankRegistry {
+    banks: Mutex<HashMap<String, RegisteredBank>>,
+}
+
+struct RegisteredBank {
+    ptr: *mut FMOD_STUDIO_BANK,
+    // Without `NONBLOCKING`, which does not change the loaded bank
+    flags: LoadBankFlags,
+    shared: Weak<SharedBank>,
+}
+
+unsafe impl Send for BankRegistry {}
+unsafe impl Sync for BankRegistry {}
+
+impl BankRegistry {
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::new(BankRegistry {
+            banks: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, RegisteredBank>> {
+        self.banks.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the bank shared for `file`, calling `load` if there is none
+    /// or if the shared bank failed to load. Fails with `InvalidParam` if the
+    /// shared bank was loaded with different `flags`.
+    pub(crate) fn share<F>(
+        registry: &Arc<Self>,
+        file: &str,
+        mut flags: LoadBankFlags,
+        load: F,
+    ) -> Result<Arc<SharedBank>>
+    where
+        F: FnOnce() -> Result<*mut FMOD_STUDIO_BANK>,
+    {
+        flags.remove(LoadBankFlags::NONBLOCKING);
+
+        let mut banks = registry.lock();
+        if let Some(registered) = banks.remove(file) {
+            if let Some(shared) = registered.shared.upgrade() {
+                let failed = match Bank::from_ptr(registered.ptr).get_loading_state() {
+                    Ok(state) => state == LoadingState::Error,
+                    Err(_) => true,
+                };
+                if !failed {
+                    let same_flags = registered.flags == flags;
+                    banks.insert(file.to_owned(), registered);
+                    return if same_flags {
+                        Ok(shared)
+                    } else {
+                        Err(FmodError::InvalidParam)
+                    };
+                }
+            }
+
+            // Either the bank failed to load in the background, or the last
+            // handle is being dropped on another thread which has not reached
+            // the lock yet. Unload the bank here so that it can be loaded
+            // again; the remaining handles then find it invalid.
+            unload_shared(registered.ptr);
+        }
+
+        let ptr = load()?;
+        let shared = Arc::new(SharedBank {
+            ptr,
+            file: file.to_owned(),
+            registry: registry.clone(),
+        });
+        banks.insert(
+            file.to_owned(),
+            RegisteredBank {
+                ptr,
+                flags,
+                shared: Arc::downgrade(&shared),
+            },
+        );
+        Ok(shared)
+    }
+
+    /// Calls `unload_all` with the registry locked, forgetting every shared
+    /// bank if it succeeds.
+    pub(crate) fn clear<F: FnOnce() -> Result<()>>(&self, unload_all: F) -> Result<()> {
+        let mut banks = self.lock();
+        unload_all()?;
+        banks.clear();
+        Ok(())
+    }
+}
+
+/// The state shared by the clones of a `BankHandle`. Dropping the last of
+/// them unloads the bank.
+pub(crate) struct SharedBank {
+    ptr: *mut FMOD_STUDIO_BANK,
+    file: String,
+    registry: Arc<BankRegistry>,
+}
+
+unsafe impl Send for SharedBank {}
+unsafe impl Sync for SharedBank {}
+
+impl Drop for SharedBank {
+    fn drop(&mut self) {
+        // Holding the registry lock keeps another thread from sharing the
+        // bank while it is unloaded.
+        let mut banks = self.registry.lock();
+
+        // `System::unload_all` or a reload may have replaced the entry
+        let registered = banks
+            .get(&self.file)
+            .is_some_and(|registered| ptr::eq(registered.shared.as_ptr(), self));
+        if registered {
+            banks.remove(&self.file);
+        }
+
+        unload_shared(self.ptr);
+    }
+}
+
+/// Unloads a shared bank unless it has been unloaded by other means already.
+fn unload_shared(ptr: *mut FMOD_STUDIO_BANK) {
+    let bank = Bank::from_ptr(ptr);
+    if !bank.is_valid() {
+        return;
+    }
+
+    bank.unload()
+        .map_err(|err| {
+            eprintln!(
+                "Error dropping fmod_studio::BankHandle: {:?}: {}",
+                err,
+                err.description()
+            );
+        })
+        .ok();
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum LoadingState {
     Unloading,
@@ -148,3 +464,58 @@ pub enum LoadingState {
     Loaded,
     Error,
 }
+
+/// Options for loading a bank. Flags are combined with `|`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LoadBankFlags {
+    bits: FMOD_STUDIO_LOAD_BANK_FLAGS,
+}
+
+impl LoadBankFlags {
+    /// Loads the bank synchronously.
+    pub const NORMAL: LoadBankFlags = LoadBankFlags {
+        bits: FMOD_STUDIO_LOAD_BANK_NORMAL,
+    };
+    /// Returns before the bank has finished loading. Check its progress
+    /// with `Bank::get_loading_state`.
+    pub const NONBLOCKING: LoadBankFlags = LoadBankFlags {
+        bits: FMOD_STUDIO_LOAD_BANK_NONBLOCKING,
+    };
+    /// Decompresses the bank's sample data when it is loaded rather than on
+    /// playback.
+    pub const DECOMPRESS_SAMPLES: LoadBankFlags = LoadBankFlags {
+        bits: FMOD_STUDIO_LOAD_BANK_DECOMPRESS_SAMPLES,
+    };
+
+    pub fn bits(&self) -> FMOD_STUDIO_LOAD_BANK_FLAGS {
+        self.bits
+    }
+
+    pub fn contains(&self, other: LoadBankFlags) -> bool {
+        self.bits & other.bits == other.bits
+    }
+
+    pub fn insert(&mut self, other: LoadBankFlags) {
+        self.bits |= other.bits;
+    }
+
+    pub fn remove(&mut self, other: LoadBankFlags) {
+        self.bits &= !other.bits;
+    }
+}
+
+impl BitOr for LoadBankFlags {
+    type Output = LoadBankFlags;
+
+    fn bitor(self, other: LoadBankFlags) -> LoadBankFlags {
+        LoadBankFlags {
+            bits: self.bits | other.bits,
+        }
+    }
+}
+
+impl BitOrAssign for LoadBankFlags {
+    fn bitor_assign(&mut self, other: LoadBankFlags) {
+        self.insert(other);
+    }
+}
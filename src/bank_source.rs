@@ -0,0 +1,284 @@
+use std::{cmp, fmt, mem, ptr, slice};
+use std::alloc::{self, Layout};
+use std::collections::HashMap;
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom};
+use std::ops::{Deref, DerefMut};
+use std::os::raw::{c_char, c_uint, c_void};
+use std::sync::Mutex;
+
+use ffi::*;
+use error::*;
+use event_callback;
+
+/// Keeps the data FMOD reads a bank from alive until the bank has been
+/// unloaded. Entries are removed by the `BANK_UNLOAD` system callback, and
+/// anything left over is dropped after the system has been released.
+pub(crate) struct BankResources {
+    resources: Mutex<HashMap<usize, Box<dyn Send>>>,
+}
+
+impl BankResources {
+    pub(crate) fn new() -> Box<Self> {
+        Box::new(BankResources {
+            resources: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub(crate) fn insert(&self, bank: *mut FMOD_STUDIO_BANK, resource: Box<dyn Send>) {
+        self.resources
+            .lock()
+            .unwrap()
+            .insert(bank as usize, resource);
+    }
+
+    fn remove(&self, bank: *mut FMOD_STUDIO_BANK) {
+        let resource = match self.resources.lock() {
+            Ok(mut resources) => resources.remove(&(bank as usize)),
+            Err(_) => None,
+        };
+        drop(resource);
+    }
+}
+
+pub(crate) unsafe extern "C" fn system_callback(
+    _system: *mut FMOD_STUDIO_SYSTEM,
+    type_: FMOD_STUDIO_SYSTEM_CALLBACK_TYPE,
+    commanddata: *mut c_void,
+    userdata: *mut c_void,
+) -> FMOD_RESULT {
+    if type_ & FMOD_STUDIO_SYSTEM_CALLBACK_BANK_UNLOAD != 0 {
+        if !userdata.is_null() {
+            let resources = &*(userdata as *const BankResources);
+            resources.remove(commanddata as *mut FMOD_STUDIO_BANK);
+        }
+        event_callback::release_callbacks();
+    }
+    FMOD_RESULT::FMOD_OK
+}
+
+/// A `Read + Seek` value a bank is loaded from by `System::load_bank_custom`.
+pub trait BankSource: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> BankSource for T {}
+
+/// Shared by every file handle FMOD opens on the source. Each handle keeps
+/// its own position, so the source is seeked before every read.
+pub(crate) struct CustomBank {
+    source: Mutex<Box<dyn BankSource>>,
+    size: c_uint,
+}
+
+struct CustomBankFile {
+    position: u64,
+}
+
+impl CustomBank {
+    pub(crate) fn new(mut source: Box<dyn BankSource>) -> Result<Box<Self>> {
+        let size = source
+            .seek(SeekFrom::End(0))
+            .map_err(|_| FmodError::FileCouldNotSeek)?;
+        if size > c_uint::MAX as u64 {
+            return Err(FmodError::FileBad);
+        }
+
+        Ok(Box::new(CustomBank {
+            source: Mutex::new(source),
+            size: size as c_uint,
+        }))
+    }
+
+    pub(crate) fn bank_info(&self) -> FMOD_STUDIO_BANK_INFO {
+        FMOD_STUDIO_BANK_INFO {
+            size: mem::size_of::<FMOD_STUDIO_BANK_INFO>() as _,
+            userdata: self as *const CustomBank as *mut c_void,
+            userdatalength: 0,
+            opencallback: Some(custom_bank_open),
+            closecallback: Some(custom_bank_close),
+            readcallback: Some(custom_bank_read),
+            seekcallback: Some(custom_bank_seek),
+        }
+    }
+
+    fn read(&self, position: u64, buffer: &mut [u8]) -> Result<usize> {
+        let mut source = self.source.lock().map_err(|_| FmodError::FileBad)?;
+        source
+            .seek(SeekFrom::Start(position))
+            .map_err(|_| FmodError::FileCouldNotSeek)?;
+
+        let mut read = 0;
+        while read < buffer.len() {
+            match source.read(&mut buffer[read..]) {
+                Ok(0) => break,
+                Ok(n) => read += n,
+                Err(ref err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(_) => return Err(FmodError::FileBad),
+            }
+        }
+        Ok(read)
+    }
+}
+
+unsafe extern "C" fn custom_bank_open(
+    _name: *const c_char,
+    filesize: *mut c_uint,
+    handle: *mut *mut c_void,
+    userdata: *mut c_void,
+) -> FMOD_RESULT {
+    let bank = &*(userdata as *const CustomBank);
+    *filesize = bank.size;
+    *handle = Box::into_raw(Box::new(CustomBankFile { position: 0 })) as *mut c_void;
+    FMOD_RESULT::FMOD_OK
+}
+
+unsafe extern "C" fn custom_bank_close(handle: *mut c_void, _userdata: *mut c_void) -> FMOD_RESULT {
+    drop(Box::from_raw(handle as *mut CustomBankFile));
+    FMOD_RESULT::FMOD_OK
+}
+
+unsafe extern "C" fn custom_bank_read(
+    handle: *mut c_void,
+    buffer: *mut c_void,
+    sizebytes: c_uint,
+    bytesread: *mut c_uint,
+    userdata: *mut c_void,
+) -> FMOD_RESULT {
+    let bank = &*(userdata as *const CustomBank);
+    let file = &mut *(handle as *mut CustomBankFile);
+    let buffer = slice::from_raw_parts_mut(buffer as *mut u8, sizebytes as usize);
+
+    let read = match bank.read(file.position, buffer) {
+        Ok(read) => read,
+        Err(err) => return err.to_fmod_result(),
+    };
+    file.position += read as u64;
+    *bytesread = read as c_uint;
+
+    if read < sizebytes as usize {
+        FMOD_RESULT::FMOD_ERR_FILE_EOF
+    } else {
+        FMOD_RESULT::FMOD_OK
+    }
+}
+
+unsafe extern "C" fn custom_bank_seek(
+    handle: *mut c_void,
+    pos: c_uint,
+    _userdata: *mut c_void,
+) -> FMOD_RESULT {
+    let file = &mut *(handle as *mut CustomBankFile);
+    file.position = pos as u64;
+    FMOD_RESULT::FMOD_OK
+}
+
+/// A heap buffer aligned to `FMOD_STUDIO_LOAD_MEMORY_ALIGNMENT`, as required
+/// by `System::load_bank_memory_point`.
+pub struct AlignedBankBuffer {
+    ptr: *mut u8,
+    len: usize,
+}
+
+unsafe impl Send for AlignedBankBuffer {}
+unsafe impl Sync for AlignedBankBuffer {}
+
+impl AlignedBankBuffer {
+    /// Allocates a zeroed buffer of `len` bytes.
+    pub fn new(len: usize) -> Self {
+        unsafe {
+            let ptr = alloc::alloc_zeroed(Self::layout(len));
+            if ptr.is_null() {
+                alloc::handle_alloc_error(Self::layout(len));
+            }
+            AlignedBankBuffer { ptr, len }
+        }
+    }
+
+    pub fn from_slice(data: &[u8]) -> Self {
+        let mut buffer = AlignedBankBuffer::new(data.len());
+        buffer.copy_from_slice(data);
+        buffer
+    }
+
+    /// Reads `reader` to the end into a new buffer.
+    pub fn from_reader<R: Read>(mut reader: R) -> io::Result<Self> {
+        // Reads straight into the aligned allocation, growing it as needed
+        let mut buffer = AlignedBankBuffer::new(0);
+        let mut filled = 0;
+        loop {
+            if filled == buffer.len {
+                let len = cmp::max(buffer.len * 2, 8 * 1024);
+                buffer.resize(len);
+            }
+            match reader.read(&mut buffer[filled..]) {
+                Ok(0) => break,
+                Ok(read) => filled += read,
+                Err(ref err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        buffer.resize(filled);
+        Ok(buffer)
+    }
+
+    /// Reallocates the buffer in place, zeroing any bytes added at the end.
+    fn resize(&mut self, len: usize) {
+        unsafe {
+            let ptr = alloc::realloc(self.ptr, Self::layout(self.len), Self::layout(len).size());
+            if ptr.is_null() {
+                alloc::handle_alloc_error(Self::layout(len));
+            }
+            if len > self.len {
+                ptr::write_bytes(ptr.add(self.len), 0, len - self.len);
+            }
+            self.ptr = ptr;
+            self.len = len;
+        }
+    }
+
+    fn layout(len: usize) -> Layout {
+        // Zero sized allocations are not allowed
+        let size = if len == 0 { 1 } else { len };
+        Layout::from_size_align(size, FMOD_STUDIO_LOAD_MEMORY_ALIGNMENT as usize).unwrap()
+    }
+}
+
+impl Deref for AlignedBankBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl DerefMut for AlignedBankBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl AsRef<[u8]> for AlignedBankBuffer {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Clone for AlignedBankBuffer {
+    fn clone(&self) -> Self {
+        AlignedBankBuffer::from_slice(self)
+    }
+}
+
+impl Drop for AlignedBankBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            alloc::dealloc(self.ptr, Self::layout(self.len));
+        }
+    }
+}
+
+impl fmt::Debug for AlignedBankBuffer {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("AlignedBankBuffer")
+            .field("len", &self.len)
+            .finish()
+    }
+}
@@ -0,0 +1,119 @@
+use std::{fmt, mem, ptr};
+use std::marker::PhantomData;
+
+use ffi::*;
+use error::*;
+use event::StopMode;
+use guid::Guid;
+use string::get_string;
+use system::System;
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct Bus<'sys> {
+    pub(crate) ptr: *mut FMOD_STUDIO_BUS,
+    system: PhantomData<&'sys System>,
+}
+
+unsafe impl<'sys> Send for Bus<'sys> {}
+unsafe impl<'sys> Sync for Bus<'sys> {}
+
+impl<'sys> Bus<'sys> {
+    pub(crate) fn from_ptr(ptr: *mut FMOD_STUDIO_BUS) -> Self {
+        Bus {
+            ptr,
+            system: PhantomData,
+        }
+    }
+
+    /// Whether the bank containing the bus is still loaded.
+    pub fn is_valid(&self) -> bool {
+        unsafe { FMOD_Studio_Bus_IsValid(self.ptr) != 0 }
+    }
+
+    pub fn get_id(&self) -> Result<Guid> {
+        unsafe {
+            let mut guid = mem::zeroed();
+
+            FMOD_Studio_Bus_GetID(self.ptr, &mut guid).to_err()?;
+
+            Ok(Guid { inner: guid })
+        }
+    }
+
+    pub fn get_path(&self) -> Result<String> {
+        get_string(|path, size, retrieved| unsafe {
+            FMOD_Studio_Bus_GetPath(self.ptr, path, size, retrieved)
+        })
+    }
+
+    /// The volume level set by the game.
+    pub fn get_volume(&self) -> Result<f32> {
+        unsafe {
+            let mut volume = 0.0;
+            FMOD_Studio_Bus_GetVolume(self.ptr, &mut volume, ptr::null_mut()).to_err()?;
+            Ok(volume)
+        }
+    }
+
+    /// The volume level after modulation and automation have been applied.
+    pub fn get_final_volume(&self) -> Result<f32> {
+        unsafe {
+            let mut volume = 0.0;
+            FMOD_Studio_Bus_GetVolume(self.ptr, ptr::null_mut(), &mut volume).to_err()?;
+            Ok(volume)
+        }
+    }
+
+    pub fn set_volume(&self, volume: f32) -> Result<()> {
+        unsafe { FMOD_Studio_Bus_SetVolume(self.ptr, volume).to_err() }
+    }
+
+    pub fn get_paused(&self) -> Result<bool> {
+        unsafe {
+            let mut paused = 0;
+            FMOD_Studio_Bus_GetPaused(self.ptr, &mut paused).to_err()?;
+            Ok(paused != 0)
+        }
+    }
+
+    pub fn set_paused(&self, paused: bool) -> Result<()> {
+        unsafe { FMOD_Studio_Bus_SetPaused(self.ptr, paused as FMOD_BOOL).to_err() }
+    }
+
+    pub fn get_mute(&self) -> Result<bool> {
+        unsafe {
+            let mut mute = 0;
+            FMOD_Studio_Bus_GetMute(self.ptr, &mut mute).to_err()?;
+            Ok(mute != 0)
+        }
+    }
+
+    pub fn set_mute(&self, mute: bool) -> Result<()> {
+        unsafe { FMOD_Studio_Bus_SetMute(self.ptr, mute as FMOD_BOOL).to_err() }
+    }
+
+    /// Stops all event instances routed into this bus.
+    pub fn stop_all_events(&self, mode: StopMode) -> Result<()> {
+        unsafe { FMOD_Studio_Bus_StopAllEvents(self.ptr, mode.to_raw()).to_err() }
+    }
+
+    /// The raw FMOD handle.
+    ///
+    /// # Safety
+    ///
+    /// The returned pointer is only valid while the `System` this `Bus` was
+    /// retrieved from lives.
+    pub unsafe fn as_ptr(&self) -> *mut FMOD_STUDIO_BUS {
+        self.ptr
+    }
+}
+
+impl<'sys> fmt::Debug for Bus<'sys> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Bus")
+            .field("path", &self.get_path())
+            .field("volume", &self.get_volume())
+            .finish()
+    }
+}
@@ -1,25 +1,263 @@
-use std::ptr;
+use std::{fmt, mem, ptr};
+use std::convert::TryFrom;
+use std::ffi::CString;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::time::Duration;
 
 use ffi::*;
 use error::*;
+use attributes::Attributes3D;
+use event_callback::{self, EventCallbackEvent};
+use guid::Guid;
+use parameter::{ParameterDescription, ParameterInstance};
+use string::get_string;
+use system::System;
+use user_property::{UserProperties, UserProperty};
 
 #[derive(Copy, Clone)]
 #[repr(C)]
-pub struct EventDescription {
+pub struct EventDescription<'sys> {
     pub(crate) ptr: *mut FMOD_STUDIO_EVENTDESCRIPTION,
+    system: PhantomData<&'sys System>,
 }
 
-unsafe impl Send for EventDescription {}
-unsafe impl Sync for EventDescription {}
+unsafe impl<'sys> Send for EventDescription<'sys> {}
+unsafe impl<'sys> Sync for EventDescription<'sys> {}
 
-impl EventDescription {
-    pub fn create_instance(&self) -> Result<EventInstance> {
+impl<'sys> EventDescription<'sys> {
+    pub(crate) fn from_ptr(ptr: *mut FMOD_STUDIO_EVENTDESCRIPTION) -> Self {
+        EventDescription {
+            ptr,
+            system: PhantomData,
+        }
+    }
+
+    /// Whether the description is still usable. It becomes invalid once the
+    /// bank containing it is unloaded, after which its methods return
+    /// `FmodError::InvalidHandle`.
+    pub fn is_valid(&self) -> bool {
+        unsafe { FMOD_Studio_EventDescription_IsValid(self.ptr) != 0 }
+    }
+
+    pub fn create_instance(&self) -> Result<EventInstance<'sys>> {
         unsafe {
             let mut ptr = ptr::null_mut();
 
             FMOD_Studio_EventDescription_CreateInstance(self.ptr, &mut ptr).to_err()?;
 
-            Ok(EventInstance { ptr })
+            Ok(EventInstance::from_ptr(ptr))
+        }
+    }
+
+    /// Creates an instance which is released when it is dropped.
+    pub fn create_owned_instance(&self) -> Result<OwnedEventInstance<'sys>> {
+        self.create_instance().map(OwnedEventInstance::new)
+    }
+
+    pub fn get_id(&self) -> Result<Guid> {
+        unsafe {
+            let mut guid = mem::zeroed();
+
+            FMOD_Studio_EventDescription_GetID(self.ptr, &mut guid).to_err()?;
+
+            Ok(Guid { inner: guid })
+        }
+    }
+
+    pub fn get_path(&self) -> Result<String> {
+        get_string(|path, size, retrieved| unsafe {
+            FMOD_Studio_EventDescription_GetPath(self.ptr, path, size, retrieved)
+        })
+    }
+
+    /// The length of the timeline. Zero for events without a timeline.
+    pub fn get_length(&self) -> Result<Duration> {
+        unsafe {
+            let mut length = 0;
+            FMOD_Studio_EventDescription_GetLength(self.ptr, &mut length).to_err()?;
+            Ok(Duration::from_millis(length as u64))
+        }
+    }
+
+    pub fn get_minimum_distance(&self) -> Result<f32> {
+        unsafe {
+            let mut distance = 0.0;
+            FMOD_Studio_EventDescription_GetMinimumDistance(self.ptr, &mut distance).to_err()?;
+            Ok(distance)
+        }
+    }
+
+    pub fn get_maximum_distance(&self) -> Result<f32> {
+        unsafe {
+            let mut distance = 0.0;
+            FMOD_Studio_EventDescription_GetMaximumDistance(self.ptr, &mut distance).to_err()?;
+            Ok(distance)
+        }
+    }
+
+    /// The largest size of the spatializers used by the event.
+    pub fn get_sound_size(&self) -> Result<f32> {
+        unsafe {
+            let mut size = 0.0;
+            FMOD_Studio_EventDescription_GetSoundSize(self.ptr, &mut size).to_err()?;
+            Ok(size)
+        }
+    }
+
+    pub fn is_snapshot(&self) -> Result<bool> {
+        unsafe {
+            let mut snapshot = 0;
+            FMOD_Studio_EventDescription_IsSnapshot(self.ptr, &mut snapshot).to_err()?;
+            Ok(snapshot != 0)
+        }
+    }
+
+    /// Whether the event is guaranteed to terminate on its own.
+    pub fn is_oneshot(&self) -> Result<bool> {
+        unsafe {
+            let mut oneshot = 0;
+            FMOD_Studio_EventDescription_IsOneshot(self.ptr, &mut oneshot).to_err()?;
+            Ok(oneshot != 0)
+        }
+    }
+
+    pub fn is_stream(&self) -> Result<bool> {
+        unsafe {
+            let mut stream = 0;
+            FMOD_Studio_EventDescription_IsStream(self.ptr, &mut stream).to_err()?;
+            Ok(stream != 0)
+        }
+    }
+
+    pub fn is_3d(&self) -> Result<bool> {
+        unsafe {
+            let mut is_3d = 0;
+            FMOD_Studio_EventDescription_Is3D(self.ptr, &mut is_3d).to_err()?;
+            Ok(is_3d != 0)
+        }
+    }
+
+    pub fn has_cue(&self) -> Result<bool> {
+        unsafe {
+            let mut cue = 0;
+            FMOD_Studio_EventDescription_HasCue(self.ptr, &mut cue).to_err()?;
+            Ok(cue != 0)
+        }
+    }
+
+    pub fn get_parameter_count(&self) -> Result<i32> {
+        unsafe {
+            let mut count = 0;
+            FMOD_Studio_EventDescription_GetParameterCount(self.ptr, &mut count).to_err()?;
+            Ok(count)
+        }
+    }
+
+    pub fn parameters(&self) -> Result<Vec<ParameterDescription>> {
+        (0..self.get_parameter_count()?)
+            .map(|index| self.parameter_by_index(index))
+            .collect()
+    }
+
+    pub fn parameter(&self, name: &str) -> Result<ParameterDescription> {
+        unsafe {
+            let mut desc = mem::zeroed();
+            let cstr = CString::new(name).unwrap();
+
+            FMOD_Studio_EventDescription_GetParameter(self.ptr, cstr.as_ptr(), &mut desc)
+                .to_err()?;
+
+            Ok(ParameterDescription::from_raw(&desc))
+        }
+    }
+
+    pub fn parameter_by_index(&self, index: i32) -> Result<ParameterDescription> {
+        unsafe {
+            let mut desc = mem::zeroed();
+
+            FMOD_Studio_EventDescription_GetParameterByIndex(self.ptr, index, &mut desc)
+                .to_err()?;
+
+            Ok(ParameterDescription::from_raw(&desc))
+        }
+    }
+
+    pub fn get_user_property_count(&self) -> Result<i32> {
+        unsafe {
+            let mut count = 0;
+            FMOD_Studio_EventDescription_GetUserPropertyCount(self.ptr, &mut count).to_err()?;
+            Ok(count)
+        }
+    }
+
+    pub fn user_property(&self, name: &str) -> Result<UserProperty> {
+        unsafe {
+            let mut prop = mem::zeroed();
+            let cstr = CString::new(name).unwrap();
+
+            FMOD_Studio_EventDescription_GetUserProperty(self.ptr, cstr.as_ptr(), &mut prop)
+                .to_err()?;
+
+            Ok(UserProperty::from_raw(&prop).1)
+        }
+    }
+
+    /// Returns the name and value of the user property at `index`.
+    pub fn user_property_by_index(&self, index: i32) -> Result<(String, UserProperty)> {
+        unsafe {
+            let mut prop = mem::zeroed();
+
+            FMOD_Studio_EventDescription_GetUserPropertyByIndex(self.ptr, index, &mut prop)
+                .to_err()?;
+
+            Ok(UserProperty::from_raw(&prop))
+        }
+    }
+
+    pub fn user_properties(&self) -> Result<UserProperties<'sys>> {
+        Ok(UserProperties {
+            desc: *self,
+            index: 0,
+            count: self.get_user_property_count()?,
+        })
+    }
+
+    /// Calls `callback` for everything that happens to instances of this
+    /// event which have no callback of their own. Replaces any closure set
+    /// before, and is dropped once the event's bank has been unloaded.
+    /// The closure may set a new callback on the event, which takes effect
+    /// once it returns.
+    pub fn set_callback<F>(&self, callback: F) -> Result<()>
+    where
+        F: FnMut(EventCallbackEvent) + Send + 'static,
+    {
+        unsafe { event_callback::set_description_callback(self.ptr, Box::new(callback)) }
+    }
+
+    pub fn get_instance_count(&self) -> Result<i32> {
+        unsafe {
+            let mut count = 0;
+            FMOD_Studio_EventDescription_GetInstanceCount(self.ptr, &mut count).to_err()?;
+            Ok(count)
+        }
+    }
+
+    pub fn get_instances(&self) -> Result<Vec<EventInstance<'sys>>> {
+        unsafe {
+            let mut count = 0;
+            FMOD_Studio_EventDescription_GetInstanceCount(self.ptr, &mut count).to_err()?;
+
+            let mut instances = Vec::with_capacity(count as usize);
+            FMOD_Studio_EventDescription_GetInstanceList(
+                self.ptr,
+                instances.as_mut_ptr() as *mut _,
+                instances.capacity() as i32,
+                &mut count,
+            ).to_err()?;
+            instances.set_len(count as usize);
+
+            Ok(instances)
         }
     }
 
@@ -28,31 +266,487 @@ impl EventDescription {
     }
 }
 
+impl<'sys> fmt::Debug for EventDescription<'sys> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("EventDescription")
+            .field("path", &self.get_path())
+            .field("length", &self.get_length())
+            .finish()
+    }
+}
+
 #[derive(Copy, Clone)]
 #[repr(C)]
-pub struct EventInstance {
+pub struct EventInstance<'sys> {
     pub(crate) ptr: *mut FMOD_STUDIO_EVENTINSTANCE,
+    system: PhantomData<&'sys System>,
 }
 
-unsafe impl Send for EventInstance {}
-unsafe impl Sync for EventInstance {}
+unsafe impl<'sys> Send for EventInstance<'sys> {}
+unsafe impl<'sys> Sync for EventInstance<'sys> {}
+
+impl<'sys> EventInstance<'sys> {
+    pub(crate) fn from_ptr(ptr: *mut FMOD_STUDIO_EVENTINSTANCE) -> Self {
+        EventInstance {
+            ptr,
+            system: PhantomData,
+        }
+    }
+
+    /// Whether the instance still exists. Instances are destroyed after being
+    /// released and stopped, or when the bank containing their event unloads.
+    pub fn is_valid(&self) -> bool {
+        unsafe { FMOD_Studio_EventInstance_IsValid(self.ptr) != 0 }
+    }
 
-impl EventInstance {
     pub fn start(&self) -> Result<()> {
         unsafe { FMOD_Studio_EventInstance_Start(self.ptr).to_err() }
     }
 
     pub fn stop(&self, immediately: bool) -> Result<()> {
         let mode = if immediately {
-            FMOD_STUDIO_STOP_MODE::FMOD_STUDIO_STOP_IMMEDIATE
+            StopMode::Immediate
         } else {
-            FMOD_STUDIO_STOP_MODE::FMOD_STUDIO_STOP_ALLOWFADEOUT
+            StopMode::AllowFadeout
         };
 
-        unsafe { FMOD_Studio_EventInstance_Stop(self.ptr, mode).to_err() }
+        unsafe { FMOD_Studio_EventInstance_Stop(self.ptr, mode.to_raw()).to_err() }
+    }
+
+    /// Marks the instance for release. It is destroyed once it has stopped,
+    /// and the handle becomes invalid.
+    pub fn release(&self) -> Result<()> {
+        unsafe { FMOD_Studio_EventInstance_Release(self.ptr).to_err() }
+    }
+
+    /// Calls `callback` for everything that happens to the instance. Replaces
+    /// any closure set before, and is dropped once the instance has been
+    /// destroyed. The closure runs on FMOD's update thread. It may set a new
+    /// callback on the instance, which takes effect once it returns.
+    pub fn set_callback<F>(&self, callback: F) -> Result<()>
+    where
+        F: FnMut(EventCallbackEvent) + Send + 'static,
+    {
+        unsafe { event_callback::set_instance_callback(self.ptr, Box::new(callback)) }
+    }
+
+    pub fn get_description(&self) -> Result<EventDescription<'sys>> {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+
+            FMOD_Studio_EventInstance_GetDescription(self.ptr, &mut ptr).to_err()?;
+
+            Ok(EventDescription::from_ptr(ptr))
+        }
+    }
+
+    pub fn playback_state(&self) -> Result<PlaybackState> {
+        unsafe {
+            let mut state = FMOD_STUDIO_PLAYBACK_STATE::FMOD_STUDIO_PLAYBACK_STOPPED;
+
+            FMOD_Studio_EventInstance_GetPlaybackState(self.ptr, &mut state).to_err()?;
+
+            let state = match state {
+                FMOD_STUDIO_PLAYBACK_STATE::FMOD_STUDIO_PLAYBACK_PLAYING => PlaybackState::Playing,
+                FMOD_STUDIO_PLAYBACK_STATE::FMOD_STUDIO_PLAYBACK_SUSTAINING => {
+                    PlaybackState::Sustaining
+                }
+                FMOD_STUDIO_PLAYBACK_STATE::FMOD_STUDIO_PLAYBACK_STOPPED => PlaybackState::Stopped,
+                FMOD_STUDIO_PLAYBACK_STATE::FMOD_STUDIO_PLAYBACK_STARTING => {
+                    PlaybackState::Starting
+                }
+                FMOD_STUDIO_PLAYBACK_STATE::FMOD_STUDIO_PLAYBACK_STOPPING => {
+                    PlaybackState::Stopping
+                }
+                _ => panic!(),
+            };
+
+            Ok(state)
+        }
+    }
+
+    pub fn get_paused(&self) -> Result<bool> {
+        unsafe {
+            let mut paused = 0;
+            FMOD_Studio_EventInstance_GetPaused(self.ptr, &mut paused).to_err()?;
+            Ok(paused != 0)
+        }
+    }
+
+    pub fn set_paused(&self, paused: bool) -> Result<()> {
+        unsafe { FMOD_Studio_EventInstance_SetPaused(self.ptr, paused as FMOD_BOOL).to_err() }
+    }
+
+    pub fn timeline_position(&self) -> Result<Duration> {
+        unsafe {
+            let mut position = 0;
+            FMOD_Studio_EventInstance_GetTimelinePosition(self.ptr, &mut position).to_err()?;
+            Ok(Duration::from_millis(position as u64))
+        }
+    }
+
+    /// Moves the timeline cursor. The position is truncated to milliseconds,
+    /// and must fit in an `i32` of them.
+    pub fn set_timeline_position(&self, position: Duration) -> Result<()> {
+        let millis = position
+            .as_secs()
+            .checked_mul(1000)
+            .and_then(|millis| millis.checked_add(position.subsec_millis() as u64))
+            .and_then(|millis| i32::try_from(millis).ok())
+            .ok_or(FmodError::InvalidParam)?;
+
+        unsafe { FMOD_Studio_EventInstance_SetTimelinePosition(self.ptr, millis).to_err() }
+    }
+
+    /// Allows the timeline cursor to move past sustain points.
+    pub fn trigger_cue(&self) -> Result<()> {
+        unsafe { FMOD_Studio_EventInstance_TriggerCue(self.ptr).to_err() }
+    }
+
+    /// Whether the instance has been virtualized due to the polyphony limit.
+    pub fn is_virtual(&self) -> Result<bool> {
+        unsafe {
+            let mut virtual_state = 0;
+            FMOD_Studio_EventInstance_IsVirtual(self.ptr, &mut virtual_state).to_err()?;
+            Ok(virtual_state != 0)
+        }
+    }
+
+    /// The volume level set by the game.
+    pub fn get_volume(&self) -> Result<f32> {
+        unsafe {
+            let mut volume = 0.0;
+            FMOD_Studio_EventInstance_GetVolume(self.ptr, &mut volume, ptr::null_mut()).to_err()?;
+            Ok(volume)
+        }
+    }
+
+    /// The volume level after modulation and automation have been applied.
+    pub fn get_final_volume(&self) -> Result<f32> {
+        unsafe {
+            let mut volume = 0.0;
+            FMOD_Studio_EventInstance_GetVolume(self.ptr, ptr::null_mut(), &mut volume).to_err()?;
+            Ok(volume)
+        }
+    }
+
+    pub fn set_volume(&self, volume: f32) -> Result<()> {
+        unsafe { FMOD_Studio_EventInstance_SetVolume(self.ptr, volume).to_err() }
+    }
+
+    /// The pitch multiplier set by the game.
+    pub fn get_pitch(&self) -> Result<f32> {
+        unsafe {
+            let mut pitch = 0.0;
+            FMOD_Studio_EventInstance_GetPitch(self.ptr, &mut pitch, ptr::null_mut()).to_err()?;
+            Ok(pitch)
+        }
+    }
+
+    /// The pitch multiplier after modulation and automation have been applied.
+    pub fn get_final_pitch(&self) -> Result<f32> {
+        unsafe {
+            let mut pitch = 0.0;
+            FMOD_Studio_EventInstance_GetPitch(self.ptr, ptr::null_mut(), &mut pitch).to_err()?;
+            Ok(pitch)
+        }
+    }
+
+    pub fn set_pitch(&self, pitch: f32) -> Result<()> {
+        unsafe { FMOD_Studio_EventInstance_SetPitch(self.ptr, pitch).to_err() }
+    }
+
+    pub fn get_property(&self, property: EventProperty) -> Result<f32> {
+        unsafe {
+            let mut value = 0.0;
+            FMOD_Studio_EventInstance_GetProperty(self.ptr, property.to_raw(), &mut value)
+                .to_err()?;
+            Ok(value)
+        }
+    }
+
+    /// Overrides a property of this instance. Setting a value of -1 reverts
+    /// the property to the value authored in FMOD Studio.
+    pub fn set_property(&self, property: EventProperty, value: f32) -> Result<()> {
+        unsafe {
+            FMOD_Studio_EventInstance_SetProperty(self.ptr, property.to_raw(), value).to_err()
+        }
+    }
+
+    /// The send level to the low level reverb instance `index`, from 0 to 3.
+    pub fn get_reverb_level(&self, index: i32) -> Result<f32> {
+        unsafe {
+            let mut level = 0.0;
+            FMOD_Studio_EventInstance_GetReverbLevel(self.ptr, index, &mut level).to_err()?;
+            Ok(level)
+        }
+    }
+
+    /// Sets the send level to the low level reverb instance `index`, from 0
+    /// to 3.
+    pub fn set_reverb_level(&self, index: i32, level: f32) -> Result<()> {
+        unsafe { FMOD_Studio_EventInstance_SetReverbLevel(self.ptr, index, level).to_err() }
+    }
+
+    pub fn get_3d_attributes(&self) -> Result<Attributes3D> {
+        unsafe {
+            let mut attributes = mem::zeroed();
+            FMOD_Studio_EventInstance_Get3DAttributes(self.ptr, &mut attributes).to_err()?;
+            Ok(attributes.into())
+        }
+    }
+
+    pub fn set_3d_attributes(&self, attributes: &Attributes3D) -> Result<()> {
+        unsafe {
+            let mut attributes = (*attributes).into();
+            FMOD_Studio_EventInstance_Set3DAttributes(self.ptr, &mut attributes).to_err()
+        }
+    }
+
+    /// The bitmask of listeners this instance is heard by. Bit `n` set
+    /// means listener `n` is used for spatialization.
+    pub fn get_listener_mask(&self) -> Result<u32> {
+        unsafe {
+            let mut mask = 0;
+            FMOD_Studio_EventInstance_GetListenerMask(self.ptr, &mut mask).to_err()?;
+            Ok(mask)
+        }
+    }
+
+    pub fn set_listener_mask(&self, mask: u32) -> Result<()> {
+        unsafe { FMOD_Studio_EventInstance_SetListenerMask(self.ptr, mask).to_err() }
+    }
+
+    pub fn get_parameter_count(&self) -> Result<i32> {
+        unsafe {
+            let mut count = 0;
+            FMOD_Studio_EventInstance_GetParameterCount(self.ptr, &mut count).to_err()?;
+            Ok(count)
+        }
+    }
+
+    pub fn get_parameter(&self, name: &str) -> Result<ParameterInstance<'sys>> {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            let cstr = CString::new(name).unwrap();
+
+            FMOD_Studio_EventInstance_GetParameter(self.ptr, cstr.as_ptr(), &mut ptr).to_err()?;
+
+            Ok(ParameterInstance::from_ptr(ptr))
+        }
+    }
+
+    pub fn get_parameter_by_index(&self, index: i32) -> Result<ParameterInstance<'sys>> {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+
+            FMOD_Studio_EventInstance_GetParameterByIndex(self.ptr, index, &mut ptr).to_err()?;
+
+            Ok(ParameterInstance::from_ptr(ptr))
+        }
+    }
+
+    pub fn get_parameter_value(&self, name: &str) -> Result<f32> {
+        unsafe {
+            let mut value = 0.0;
+            let cstr = CString::new(name).unwrap();
+
+            FMOD_Studio_EventInstance_GetParameterValue(
+                self.ptr,
+                cstr.as_ptr(),
+                &mut value,
+                ptr::null_mut(),
+            ).to_err()?;
+
+            Ok(value)
+        }
+    }
+
+    pub fn set_parameter_value(&self, name: &str, value: f32) -> Result<()> {
+        unsafe {
+            let cstr = CString::new(name).unwrap();
+
+            FMOD_Studio_EventInstance_SetParameterValue(self.ptr, cstr.as_ptr(), value).to_err()
+        }
+    }
+
+    pub fn get_parameter_value_by_index(&self, index: i32) -> Result<f32> {
+        unsafe {
+            let mut value = 0.0;
+
+            FMOD_Studio_EventInstance_GetParameterValueByIndex(
+                self.ptr,
+                index,
+                &mut value,
+                ptr::null_mut(),
+            ).to_err()?;
+
+            Ok(value)
+        }
+    }
+
+    pub fn set_parameter_value_by_index(&self, index: i32, value: f32) -> Result<()> {
+        unsafe {
+            FMOD_Studio_EventInstance_SetParameterValueByIndex(self.ptr, index, value).to_err()
+        }
     }
 
     pub unsafe fn as_ptr(&self) -> *mut FMOD_STUDIO_EVENTINSTANCE {
         self.ptr
     }
 }
+
+/// An `EventInstance` which is released when dropped. What happens to the
+/// sound on drop is controlled by its `DropBehavior`.
+pub struct OwnedEventInstance<'sys> {
+    instance: EventInstance<'sys>,
+    drop_behavior: DropBehavior,
+}
+
+impl<'sys> OwnedEventInstance<'sys> {
+    /// Takes ownership of `instance`, which will be released when the
+    /// returned value is dropped. Stops immediately on drop by default.
+    pub fn new(instance: EventInstance<'sys>) -> Self {
+        OwnedEventInstance {
+            instance,
+            drop_behavior: DropBehavior::StopImmediate,
+        }
+    }
+
+    pub fn drop_behavior(&self) -> DropBehavior {
+        self.drop_behavior
+    }
+
+    pub fn set_drop_behavior(&mut self, behavior: DropBehavior) {
+        self.drop_behavior = behavior;
+    }
+
+    pub fn with_drop_behavior(mut self, behavior: DropBehavior) -> Self {
+        self.drop_behavior = behavior;
+        self
+    }
+
+    /// Starts the instance and releases it, leaving it to play out and be
+    /// destroyed by FMOD once it stops. The instance is released even if it
+    /// fails to start.
+    pub fn into_fire_and_forget(self) -> Result<()> {
+        let instance = self.into_inner();
+        let started = instance.start();
+        instance.release()?;
+        started
+    }
+
+    /// Gives up ownership without releasing the instance.
+    pub fn into_inner(self) -> EventInstance<'sys> {
+        let instance = self.instance;
+        mem::forget(self);
+        instance
+    }
+}
+
+impl<'sys> Deref for OwnedEventInstance<'sys> {
+    type Target = EventInstance<'sys>;
+
+    fn deref(&self) -> &EventInstance<'sys> {
+        &self.instance
+    }
+}
+
+impl<'sys> Drop for OwnedEventInstance<'sys> {
+    fn drop(&mut self) {
+        // Already destroyed along with its bank
+        if !self.instance.is_valid() {
+            return;
+        }
+
+        let result = match self.drop_behavior {
+            DropBehavior::StopImmediate => self.instance.stop(true),
+            DropBehavior::StopAllowFadeout => self.instance.stop(false),
+            DropBehavior::KeepPlaying => Ok(()),
+        };
+
+        result
+            .and_then(|_| self.instance.release())
+            .map_err(|err| {
+                eprintln!(
+                    "Error dropping fmod_studio::OwnedEventInstance: {:?}: {}",
+                    err,
+                    err.description()
+                );
+            })
+            .ok();
+    }
+}
+
+/// What an `OwnedEventInstance` does with its sound when it is dropped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DropBehavior {
+    /// Stop the instance immediately before releasing it.
+    StopImmediate,
+    /// Stop the instance, letting it fade out before it is destroyed.
+    StopAllowFadeout,
+    /// Release the instance without stopping it. It is destroyed once it
+    /// finishes playing, like a one-shot.
+    KeepPlaying,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PlaybackState {
+    Playing,
+    /// Paused on a sustain point, waiting for `EventInstance::trigger_cue`.
+    Sustaining,
+    Stopped,
+    /// Started, but not yet playing (e.g. while sample data loads).
+    Starting,
+    /// Stopping, but still fading out or playing DSP tails.
+    Stopping,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EventProperty {
+    /// Priority to set on low level channels created by this instance, from
+    /// 0 (most important) to 256 (least important). -1 uses the event's
+    /// priority.
+    ChannelPriority,
+    /// Schedule delay in DSP clocks for synchronized playback.
+    ScheduleDelay,
+    /// Schedule look-ahead in DSP clocks on the sound timeline.
+    ScheduleLookahead,
+    /// Override for the event's 3D minimum distance.
+    MinimumDistance,
+    /// Override for the event's 3D maximum distance.
+    MaximumDistance,
+}
+
+impl EventProperty {
+    pub(crate) fn to_raw(self) -> FMOD_STUDIO_EVENT_PROPERTY {
+        use ffi::FMOD_STUDIO_EVENT_PROPERTY::*;
+        use self::EventProperty::*;
+
+        match self {
+            ChannelPriority => FMOD_STUDIO_EVENT_PROPERTY_CHANNELPRIORITY,
+            ScheduleDelay => FMOD_STUDIO_EVENT_PROPERTY_SCHEDULE_DELAY,
+            ScheduleLookahead => FMOD_STUDIO_EVENT_PROPERTY_SCHEDULE_LOOKAHEAD,
+            MinimumDistance => FMOD_STUDIO_EVENT_PROPERTY_MINIMUM_DISTANCE,
+            MaximumDistance => FMOD_STUDIO_EVENT_PROPERTY_MAXIMUM_DISTANCE,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StopMode {
+    /// Allows AHDSR modulators to complete their release, and DSP effect
+    /// tails to play out.
+    AllowFadeout,
+    /// Stops the event instance immediately.
+    Immediate,
+}
+
+impl StopMode {
+    pub(crate) fn to_raw(self) -> FMOD_STUDIO_STOP_MODE {
+        match self {
+            StopMode::AllowFadeout => FMOD_STUDIO_STOP_MODE::FMOD_STUDIO_STOP_ALLOWFADEOUT,
+            StopMode::Immediate => FMOD_STUDIO_STOP_MODE::FMOD_STUDIO_STOP_IMMEDIATE,
+        }
+    }
+}
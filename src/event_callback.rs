@@ -0,0 +1,377 @@
+use std::ptr;
+use std::collections::BTreeMap;
+use std::os::raw::c_void;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use ffi::*;
+use error::*;
+use string::string_from_ptr;
+
+/// Something that happened to an event instance, passed to the closures set
+/// with `EventInstance::set_callback` and `EventDescription::set_callback`.
+#[derive(Debug)]
+pub enum EventCallbackEvent<'a> {
+    Created,
+    /// The instance has been destroyed. No further callbacks are made for it.
+    Destroyed,
+    /// `start` was called on the instance.
+    Starting,
+    /// The instance has started playing.
+    Started,
+    /// `start` was called on an instance which was already playing.
+    Restarted,
+    Stopped,
+    /// The instance could not be started, usually because of polyphony
+    /// limits.
+    StartFailed,
+    /// A programmer instrument needs a sound, which should be set with
+    /// `ProgrammerSound::set_sound`.
+    CreateProgrammerSound(ProgrammerSound<'a>),
+    /// The sound created for a programmer instrument is no longer used and
+    /// may be released.
+    DestroyProgrammerSound(ProgrammerSound<'a>),
+    PluginCreated { name: String, dsp: *mut FMOD_DSP },
+    PluginDestroyed { name: String, dsp: *mut FMOD_DSP },
+    /// The timeline passed a named marker.
+    TimelineMarker { name: String, position: Duration },
+    /// The timeline hit a beat in a tempo section.
+    TimelineBeat {
+        bar: i32,
+        beat: i32,
+        position: Duration,
+        tempo: f32,
+        time_signature_upper: i32,
+        time_signature_lower: i32,
+    },
+    SoundPlayed(*mut FMOD_SOUND),
+    SoundStopped(*mut FMOD_SOUND),
+}
+
+/// The sound of a programmer instrument, passed with
+/// `EventCallbackEvent::CreateProgrammerSound` and `DestroyProgrammerSound`.
+#[derive(Debug)]
+pub struct ProgrammerSound<'a> {
+    props: &'a mut FMOD_STUDIO_PROGRAMMER_SOUND_PROPERTIES,
+}
+
+impl<'a> ProgrammerSound<'a> {
+    /// The name of the programmer instrument, or the key of the audio table
+    /// entry it plays.
+    pub fn name(&self) -> String {
+        unsafe { string_from_ptr(self.props.name) }
+    }
+
+    pub fn sound(&self) -> *mut FMOD_SOUND {
+        self.props.sound
+    }
+
+    pub fn subsound_index(&self) -> i32 {
+        self.props.subsoundIndex
+    }
+
+    /// Sets the sound the instrument plays.
+    ///
+    /// # Safety
+    ///
+    /// `sound` must be a valid low level sound, or null to play nothing, and
+    /// stay alive until it is passed back with `DestroyProgrammerSound`.
+    pub unsafe fn set_sound(&mut self, sound: *mut FMOD_SOUND, subsound_index: i32) {
+        self.props.sound = sound;
+        self.props.subsoundIndex = subsound_index;
+    }
+}
+
+impl<'a> EventCallbackEvent<'a> {
+    /// Returns `None` for callback types this version does not know about.
+    unsafe fn from_raw(
+        type_: FMOD_STUDIO_EVENT_CALLBACK_TYPE,
+        parameters: *mut c_void,
+    ) -> Option<Self> {
+        use self::EventCallbackEvent::*;
+
+        let event = match type_ {
+            FMOD_STUDIO_EVENT_CALLBACK_CREATED => Created,
+            FMOD_STUDIO_EVENT_CALLBACK_DESTROYED => Destroyed,
+            FMOD_STUDIO_EVENT_CALLBACK_STARTING => Starting,
+            FMOD_STUDIO_EVENT_CALLBACK_STARTED => Started,
+            FMOD_STUDIO_EVENT_CALLBACK_RESTARTED => Restarted,
+            FMOD_STUDIO_EVENT_CALLBACK_STOPPED => Stopped,
+            FMOD_STUDIO_EVENT_CALLBACK_START_FAILED => StartFailed,
+            FMOD_STUDIO_EVENT_CALLBACK_CREATE_PROGRAMMER_SOUND => {
+                CreateProgrammerSound(ProgrammerSound {
+                    props: &mut *(parameters as *mut FMOD_STUDIO_PROGRAMMER_SOUND_PROPERTIES),
+                })
+            }
+            FMOD_STUDIO_EVENT_CALLBACK_DESTROY_PROGRAMMER_SOUND => {
+                DestroyProgrammerSound(ProgrammerSound {
+                    props: &mut *(parameters as *mut FMOD_STUDIO_PROGRAMMER_SOUND_PROPERTIES),
+                })
+            }
+            FMOD_STUDIO_EVENT_CALLBACK_PLUGIN_CREATED => {
+                let props = &*(parameters as *const FMOD_STUDIO_PLUGIN_INSTANCE_PROPERTIES);
+                PluginCreated {
+                    name: string_from_ptr(props.name),
+                    dsp: props.dsp,
+                }
+            }
+            FMOD_STUDIO_EVENT_CALLBACK_PLUGIN_DESTROYED => {
+                let props = &*(parameters as *const FMOD_STUDIO_PLUGIN_INSTANCE_PROPERTIES);
+                PluginDestroyed {
+                    name: string_from_ptr(props.name),
+                    dsp: props.dsp,
+                }
+            }
+            FMOD_STUDIO_EVENT_CALLBACK_TIMELINE_MARKER => {
+                let props = &*(parameters as *const FMOD_STUDIO_TIMELINE_MARKER_PROPERTIES);
+                TimelineMarker {
+                    name: string_from_ptr(props.name),
+                    position: Duration::from_millis(props.position as u64),
+                }
+            }
+            FMOD_STUDIO_EVENT_CALLBACK_TIMELINE_BEAT => {
+                let props = &*(parameters as *const FMOD_STUDIO_TIMELINE_BEAT_PROPERTIES);
+                TimelineBeat {
+                    bar: props.bar,
+                    beat: props.beat,
+                    position: Duration::from_millis(props.position as u64),
+                    tempo: props.tempo,
+                    time_signature_upper: props.timesignatureupper,
+                    time_signature_lower: props.timesignaturelower,
+                }
+            }
+            FMOD_STUDIO_EVENT_CALLBACK_SOUND_PLAYED => SoundPlayed(parameters as *mut FMOD_SOUND),
+            FMOD_STUDIO_EVENT_CALLBACK_SOUND_STOPPED => SoundStopped(parameters as *mut FMOD_SOUND),
+            _ => return None,
+        };
+        Some(event)
+    }
+}
+
+type Callback = Box<dyn FnMut(EventCallbackEvent) + Send>;
+
+/// Held by `INSTANCE_CALLBACKS` or `DESCRIPTION_CALLBACKS` and pointed to
+/// by the user data of its instance or description. The closure can be
+/// replaced, but the state itself stays at the same address until the
+/// instance is destroyed or the description's bank is unloaded. A call in
+/// progress holds its own reference, so removing the entry never frees a
+/// state which is still running.
+struct CallbackState {
+    // Empty while the closure runs, so that it can replace itself
+    callback: Mutex<Option<Callback>>,
+}
+
+impl CallbackState {
+    unsafe fn call(
+        &self,
+        type_: FMOD_STUDIO_EVENT_CALLBACK_TYPE,
+        parameters: *mut c_void,
+    ) {
+        let event = match EventCallbackEvent::from_raw(type_, parameters) {
+            Some(event) => event,
+            None => return,
+        };
+        // Events raised while the closure runs, by the closure itself or on
+        // another thread, find it taken and are skipped
+        let mut callback = match self.lock().take() {
+            Some(callback) => callback,
+            None => return,
+        };
+        callback(event);
+
+        // Put it back unless a new closure was set while it ran
+        let mut current = self.lock();
+        if current.is_none() {
+            *current = Some(callback);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Callback>> {
+        self.callback.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// Owns the callback states of instances, keyed by instance. Entries are
+// removed by the instance's `DESTROYED` callback, or by
+// `release_callbacks` once the instance is no longer valid.
+static INSTANCE_CALLBACKS: Mutex<BTreeMap<usize, Arc<CallbackState>>> =
+    Mutex::new(BTreeMap::new());
+
+// Owns the callback states of descriptions, keyed by description, since
+// FMOD gives no notice when a description goes away.
+static DESCRIPTION_CALLBACKS: Mutex<BTreeMap<usize, Arc<CallbackState>>> =
+    Mutex::new(BTreeMap::new());
+
+pub(crate) unsafe fn set_instance_callback(
+    instance: *mut FMOD_STUDIO_EVENTINSTANCE,
+    callback: Callback,
+) -> Result<()> {
+    let mut callbacks = INSTANCE_CALLBACKS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Some(state) = callbacks.get(&(instance as usize)) {
+        // The entry may be left over from a released system's instance at
+        // the same address
+        let mut userdata = ptr::null_mut();
+        FMOD_Studio_EventInstance_GetUserData(instance, &mut userdata).to_err()?;
+        if ptr::eq(userdata as *const CallbackState, Arc::as_ptr(state)) {
+            *state.lock() = Some(callback);
+            return Ok(());
+        }
+    }
+
+    let state = Arc::new(CallbackState {
+        callback: Mutex::new(Some(callback)),
+    });
+    FMOD_Studio_EventInstance_SetUserData(instance, Arc::as_ptr(&state) as *mut _)
+        .to_err()?;
+    let result = FMOD_Studio_EventInstance_SetCallback(
+        instance,
+        Some(instance_callback),
+        FMOD_STUDIO_EVENT_CALLBACK_ALL,
+    ).to_err();
+    match result {
+        Ok(()) => {
+            callbacks.insert(instance as usize, state);
+        }
+        Err(_) => {
+            FMOD_Studio_EventInstance_SetUserData(instance, ptr::null_mut())
+                .to_err()
+                .ok();
+        }
+    }
+    result
+}
+
+pub(crate) unsafe fn set_description_callback(
+    description: *mut FMOD_STUDIO_EVENTDESCRIPTION,
+    callback: Callback,
+) -> Result<()> {
+    let mut callbacks = DESCRIPTION_CALLBACKS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Some(state) = callbacks.get(&(description as usize)) {
+        // The entry may be left over from an unloaded description at the
+        // same address
+        let mut userdata = ptr::null_mut();
+        FMOD_Studio_EventDescription_GetUserData(description, &mut userdata).to_err()?;
+        if ptr::eq(userdata as *const CallbackState, Arc::as_ptr(state)) {
+            *state.lock() = Some(callback);
+            return Ok(());
+        }
+    }
+
+    let state = Arc::new(CallbackState {
+        callback: Mutex::new(Some(callback)),
+    });
+    FMOD_Studio_EventDescription_SetUserData(description, Arc::as_ptr(&state) as *mut _)
+        .to_err()?;
+    let result = FMOD_Studio_EventDescription_SetCallback(
+        description,
+        Some(description_callback),
+        FMOD_STUDIO_EVENT_CALLBACK_ALL,
+    ).to_err();
+    match result {
+        Ok(()) => {
+            callbacks.insert(description as usize, state);
+        }
+        Err(_) => {
+            FMOD_Studio_EventDescription_SetUserData(description, ptr::null_mut())
+                .to_err()
+                .ok();
+        }
+    }
+    result
+}
+
+/// Drops the callbacks of descriptions whose banks have been unloaded, and
+/// of instances which were released without being destroyed, such as those
+/// of a released system.
+pub(crate) fn release_callbacks() {
+    let mut callbacks = DESCRIPTION_CALLBACKS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let invalid: Vec<usize> = callbacks
+        .keys()
+        .cloned()
+        .filter(|&description| unsafe {
+            FMOD_Studio_EventDescription_IsValid(description as *mut _) == 0
+        })
+        .collect();
+    for description in invalid {
+        callbacks.remove(&description);
+    }
+
+    let mut callbacks = INSTANCE_CALLBACKS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let invalid: Vec<usize> = callbacks
+        .keys()
+        .cloned()
+        .filter(|&instance| unsafe {
+            FMOD_Studio_EventInstance_IsValid(instance as *mut _) == 0
+        })
+        .collect();
+    for instance in invalid {
+        callbacks.remove(&instance);
+    }
+}
+
+unsafe extern "C" fn instance_callback(
+    type_: FMOD_STUDIO_EVENT_CALLBACK_TYPE,
+    event: *mut FMOD_STUDIO_EVENTINSTANCE,
+    parameters: *mut c_void,
+) -> FMOD_RESULT {
+    // The lock is not held while the closure runs, so that it can set
+    // callbacks itself
+    let state = {
+        let callbacks = INSTANCE_CALLBACKS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        match callbacks.get(&(event as usize)) {
+            Some(state) => state.clone(),
+            None => return FMOD_RESULT::FMOD_OK,
+        }
+    };
+
+    state.call(type_, parameters);
+
+    if type_ == FMOD_STUDIO_EVENT_CALLBACK_DESTROYED {
+        let mut callbacks = INSTANCE_CALLBACKS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let current = callbacks
+            .get(&(event as usize))
+            .is_some_and(|current| Arc::ptr_eq(current, &state));
+        if current {
+            FMOD_Studio_EventInstance_SetUserData(event, ptr::null_mut()).to_err().ok();
+            callbacks.remove(&(event as usize));
+        }
+    }
+    FMOD_RESULT::FMOD_OK
+}
+
+unsafe extern "C" fn description_callback(
+    type_: FMOD_STUDIO_EVENT_CALLBACK_TYPE,
+    event: *mut FMOD_STUDIO_EVENTINSTANCE,
+    parameters: *mut c_void,
+) -> FMOD_RESULT {
+    let mut description = ptr::null_mut();
+    let result = FMOD_Studio_EventInstance_GetDescription(event, &mut description);
+    if result != FMOD_RESULT::FMOD_OK {
+        return FMOD_RESULT::FMOD_OK;
+    }
+
+    let state = {
+        let callbacks = DESCRIPTION_CALLBACKS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        match callbacks.get(&(description as usize)) {
+            Some(state) => state.clone(),
+            None => return FMOD_RESULT::FMOD_OK,
+        }
+    };
+
+    state.call(type_, parameters);
+    FMOD_RESULT::FMOD_OK
+}
@@ -1,13 +1,36 @@
-pub use bank::Bank;
+pub use attributes::{Attributes3D, Vector3};
+pub use bank::{Bank, BankHandle, BankStrings, LoadBankFlags};
+pub use bank_source::{AlignedBankBuffer, BankSource};
+pub use bus::Bus;
 pub use error::{FmodError, Result, ToError};
-pub use event::{EventDescription, EventInstance};
+pub use event::{DropBehavior, EventDescription, EventInstance, EventProperty, OwnedEventInstance,
+                PlaybackState, StopMode};
+pub use event_callback::{EventCallbackEvent, ProgrammerSound};
 pub use guid::Guid;
-pub use system::System;
+pub use loading::{BankLoad, SampleDataLoad};
+pub use output::{capture_output, CaptureOutput, CaptureReader, Output, OutputFormat, OutputMode};
+pub use parameter::{ParameterDescription, ParameterInstance, ParameterType};
+pub use render::{OfflineRenderer, RenderScript, RenderedAudio};
+pub use system::{ListenerId, OutputType, System, SystemBuilder};
+pub use user_property::{UserProperties, UserProperty};
+pub use vca::Vca;
 
 pub mod ffi;
 
+pub mod attributes;
 pub mod bank;
+pub mod bank_source;
+pub mod bus;
 pub mod error;
 pub mod event;
+pub mod event_callback;
 pub mod guid;
+pub mod loading;
+pub mod output;
+pub mod parameter;
+pub mod render;
 pub mod system;
+pub mod user_property;
+pub mod vca;
+
+mod string;
@@ -0,0 +1,100 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use error::*;
+use bank::{Bank, LoadingState};
+use system::System;
+
+/// Resolves to the bank once a load started by `System::load_bank_file_async`
+/// has finished. Progress is checked whenever `System::update` is called.
+#[must_use = "futures do nothing unless polled"]
+pub struct BankLoad<'sys> {
+    system: &'sys System,
+    bank: Result<Bank<'sys>>,
+}
+
+impl<'sys> BankLoad<'sys> {
+    pub(crate) fn new(system: &'sys System, bank: Result<Bank<'sys>>) -> Self {
+        BankLoad { system, bank }
+    }
+}
+
+impl<'sys> Future for BankLoad<'sys> {
+    type Output = Result<Bank<'sys>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let bank = match self.bank {
+            Ok(bank) => bank,
+            Err(err) => return Poll::Ready(Err(err)),
+        };
+
+        poll_loading(self.system, bank.get_loading_state(), cx).map(|result| result.map(|_| bank))
+    }
+}
+
+/// Resolves once the sample data requested by
+/// `System::load_sample_data_async` has finished loading. Progress is checked
+/// whenever `System::update` is called. Fails with
+/// `FmodError::StudioNotLoaded` if the sample data is unloaded again after
+/// it has been seen loading.
+#[must_use = "futures do nothing unless polled"]
+pub struct SampleDataLoad<'sys> {
+    system: &'sys System,
+    bank: Bank<'sys>,
+    requested: Result<()>,
+    // Set once the request has been processed, after which the sample data
+    // only goes back to `Unloaded` if it is unloaded again
+    in_progress: bool,
+}
+
+impl<'sys> SampleDataLoad<'sys> {
+    pub(crate) fn new(system: &'sys System, bank: Bank<'sys>, requested: Result<()>) -> Self {
+        SampleDataLoad {
+            system,
+            bank,
+            requested,
+            in_progress: false,
+        }
+    }
+}
+
+impl<'sys> Future for SampleDataLoad<'sys> {
+    type Output = Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if let Err(err) = self.requested {
+            return Poll::Ready(Err(err));
+        }
+
+        let state = self.bank.get_sample_loading_state();
+        match state {
+            Ok(LoadingState::Loading) => self.in_progress = true,
+            Ok(LoadingState::Unloading) | Ok(LoadingState::Unloaded) if self.in_progress => {
+                return Poll::Ready(Err(FmodError::StudioNotLoaded));
+            }
+            _ => {}
+        }
+
+        poll_loading(self.system, state, cx)
+    }
+}
+
+fn poll_loading(
+    system: &System,
+    state: Result<LoadingState>,
+    cx: &mut Context,
+) -> Poll<Result<()>> {
+    match state {
+        Ok(LoadingState::Loaded) => Poll::Ready(Ok(())),
+        // FMOD returns the cause of a failed load from the state getters, so
+        // this should not normally be reached
+        Ok(LoadingState::Error) => Poll::Ready(Err(FmodError::Internal)),
+        // The request may not have been processed yet
+        Ok(_) => {
+            system.register_waker(cx.waker());
+            Poll::Pending
+        }
+        Err(err) => Poll::Ready(Err(err)),
+    }
+}
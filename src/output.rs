@@ -0,0 +1,365 @@
+use std::{cmp, fmt, mem, ptr, thread};
+use std::os::raw::{c_char, c_int, c_void};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use ffi::*;
+use error::*;
+
+/// Receives the final mix of a `System` created with
+/// `SystemBuilder::custom_output`.
+pub trait Output: Send + 'static {
+    /// Called while the system initializes, before any audio is mixed.
+    fn init(&mut self, format: OutputFormat) {
+        let _ = format;
+    }
+
+    /// Receives each block of the final mix as interleaved samples.
+    fn mix(&mut self, samples: &[f32]);
+}
+
+/// Holds the output passed to `SystemBuilder::custom_output` until a system
+/// is built. Clones of the builder share it, and only the first system built
+/// gets it, since an output cannot be fed by two mixers.
+#[derive(Clone)]
+pub(crate) struct PendingOutput {
+    output: Arc<Mutex<Option<Box<dyn Output>>>>,
+    mode: OutputMode,
+}
+
+impl PendingOutput {
+    pub(crate) fn new(output: Box<dyn Output>, mode: OutputMode) -> Self {
+        PendingOutput {
+            output: Arc::new(Mutex::new(Some(output))),
+            mode,
+        }
+    }
+
+    /// Fails with `OutputAllocated` once a system has taken the output.
+    pub(crate) fn take(&self) -> Result<Box<OutputPlugin>> {
+        let output = self.output
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+            .ok_or(FmodError::OutputAllocated)?;
+        Ok(OutputPlugin::new(output, self.mode))
+    }
+}
+
+impl fmt::Debug for PendingOutput {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("PendingOutput")
+            .field("mode", &self.mode)
+            .finish()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OutputFormat {
+    pub sample_rate: i32,
+    pub channels: i32,
+    /// The number of sample frames in each mixed block.
+    pub block_length: u32,
+}
+
+/// Decides what drives the mixer of a custom output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OutputMode {
+    /// FMOD's mixer thread mixes a block whenever one is due in real time.
+    Realtime,
+    /// A single block is mixed on every call to `System::update`.
+    NonRealtime,
+}
+
+pub(crate) struct OutputPlugin {
+    output: Box<dyn Output>,
+    mode: OutputMode,
+    desc: FMOD_OUTPUT_DESCRIPTION,
+    format: OutputFormat,
+    buffer: Vec<f32>,
+    started: Option<Instant>,
+    mixed_frames: u64,
+}
+
+impl OutputPlugin {
+    pub(crate) fn new(output: Box<dyn Output>, mode: OutputMode) -> Box<Self> {
+        let mut desc: FMOD_OUTPUT_DESCRIPTION = unsafe { mem::zeroed() };
+        desc.apiversion = FMOD_OUTPUT_PLUGIN_VERSION;
+        desc.name = PLUGIN_NAME.as_ptr() as *const c_char;
+        desc.polling = 0;
+        desc.getnumdrivers = Some(output_get_num_drivers);
+        desc.getdriverinfo = Some(output_get_driver_info);
+        desc.init = Some(output_init);
+        match mode {
+            OutputMode::Realtime => desc.mixer = Some(output_mixer),
+            OutputMode::NonRealtime => desc.update = Some(output_update),
+        }
+
+        Box::new(OutputPlugin {
+            output,
+            mode,
+            desc,
+            format: OutputFormat {
+                sample_rate: 0,
+                channels: 0,
+                block_length: 0,
+            },
+            buffer: Vec::new(),
+            started: None,
+            mixed_frames: 0,
+        })
+    }
+
+    pub(crate) fn description(&self) -> &FMOD_OUTPUT_DESCRIPTION {
+        &self.desc
+    }
+
+    unsafe fn mix_block(&mut self, state: *mut FMOD_OUTPUT_STATE) -> FMOD_RESULT {
+        let read = match (*state).readfrommixer {
+            Some(read) => read,
+            None => return FMOD_RESULT::FMOD_ERR_INTERNAL,
+        };
+
+        let result = read(
+            state,
+            self.buffer.as_mut_ptr() as *mut c_void,
+            self.format.block_length,
+        );
+        if result != FMOD_RESULT::FMOD_OK {
+            return result;
+        }
+
+        self.mixed_frames += self.format.block_length as u64;
+        self.output.mix(&self.buffer);
+
+        FMOD_RESULT::FMOD_OK
+    }
+}
+
+impl fmt::Debug for OutputPlugin {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("OutputPlugin")
+            .field("mode", &self.mode)
+            .field("format", &self.format)
+            .finish()
+    }
+}
+
+const PLUGIN_NAME: &[u8] = b"fmod-studio output\0";
+
+unsafe extern "C" fn output_get_num_drivers(
+    _state: *mut FMOD_OUTPUT_STATE,
+    numdrivers: *mut c_int,
+) -> FMOD_RESULT {
+    *numdrivers = 1;
+    FMOD_RESULT::FMOD_OK
+}
+
+unsafe extern "C" fn output_get_driver_info(
+    _state: *mut FMOD_OUTPUT_STATE,
+    _id: c_int,
+    name: *mut c_char,
+    namelen: c_int,
+    _guid: *mut FMOD_GUID,
+    systemrate: *mut c_int,
+    speakermode: *mut FMOD_SPEAKERMODE,
+    speakermodechannels: *mut c_int,
+) -> FMOD_RESULT {
+    if !name.is_null() && namelen > 0 {
+        let len = cmp::min(PLUGIN_NAME.len(), namelen as usize);
+        ptr::copy_nonoverlapping(PLUGIN_NAME.as_ptr() as *const c_char, name, len);
+        *name.offset(len as isize - 1) = 0;
+    }
+    if !systemrate.is_null() {
+        *systemrate = 48000;
+    }
+    if !speakermode.is_null() {
+        *speakermode = FMOD_SPEAKERMODE::FMOD_SPEAKERMODE_STEREO;
+    }
+    if !speakermodechannels.is_null() {
+        *speakermodechannels = 2;
+    }
+    FMOD_RESULT::FMOD_OK
+}
+
+unsafe extern "C" fn output_init(
+    state: *mut FMOD_OUTPUT_STATE,
+    _selecteddriver: c_int,
+    _flags: FMOD_INITFLAGS,
+    outputrate: *mut c_int,
+    speakermode: *mut FMOD_SPEAKERMODE,
+    speakermodechannels: *mut c_int,
+    outputformat: *mut FMOD_SOUND_FORMAT,
+    dspbufferlength: c_int,
+    _dspnumbuffers: c_int,
+    extradriverdata: *mut c_void,
+) -> FMOD_RESULT {
+    if extradriverdata.is_null() {
+        return FMOD_RESULT::FMOD_ERR_INTERNAL;
+    }
+    (*state).plugindata = extradriverdata;
+    *outputformat = FMOD_SOUND_FORMAT::FMOD_SOUND_FORMAT_PCMFLOAT;
+    if *speakermodechannels <= 0 {
+        *speakermode = FMOD_SPEAKERMODE::FMOD_SPEAKERMODE_STEREO;
+        *speakermodechannels = 2;
+    }
+
+    let plugin = &mut *(extradriverdata as *mut OutputPlugin);
+    plugin.format = OutputFormat {
+        sample_rate: *outputrate,
+        channels: *speakermodechannels,
+        block_length: dspbufferlength as u32,
+    };
+    plugin.buffer = vec![0.0; dspbufferlength as usize * *speakermodechannels as usize];
+    plugin.output.init(plugin.format);
+
+    FMOD_RESULT::FMOD_OK
+}
+
+unsafe extern "C" fn output_update(state: *mut FMOD_OUTPUT_STATE) -> FMOD_RESULT {
+    let plugin = &mut *((*state).plugindata as *mut OutputPlugin);
+    plugin.mix_block(state)
+}
+
+unsafe extern "C" fn output_mixer(state: *mut FMOD_OUTPUT_STATE) -> FMOD_RESULT {
+    let plugin = &mut *((*state).plugindata as *mut OutputPlugin);
+    if plugin.format.sample_rate <= 0 {
+        return FMOD_RESULT::FMOD_ERR_INTERNAL;
+    }
+
+    // Pace the mixer so each block is produced when it would have started
+    // playing, waking at least every 200ms as FMOD asks.
+    let started = *plugin.started.get_or_insert_with(Instant::now);
+    let rate = plugin.format.sample_rate as u64;
+    let due = started + Duration::new(
+        plugin.mixed_frames / rate,
+        ((plugin.mixed_frames % rate) * 1_000_000_000 / rate) as u32,
+    );
+    let now = Instant::now();
+    if due > now {
+        let wait = due - now;
+        let max_wait = Duration::from_millis(200);
+        thread::sleep(cmp::min(wait, max_wait));
+        if wait > max_wait {
+            return FMOD_RESULT::FMOD_OK;
+        }
+    }
+
+    plugin.mix_block(state)
+}
+
+/// Creates a `CaptureOutput` and the `CaptureReader` which receives its
+/// samples. `capacity` is the size of the ring buffer in samples.
+pub fn capture_output(capacity: usize) -> (CaptureOutput, CaptureReader) {
+    let ring = Arc::new(RingBuffer {
+        data: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+        write: AtomicUsize::new(0),
+        read: AtomicUsize::new(0),
+        sample_rate: AtomicUsize::new(0),
+        channels: AtomicUsize::new(0),
+        dropped: AtomicUsize::new(0),
+    });
+
+    (
+        CaptureOutput { ring: ring.clone() },
+        CaptureReader { ring },
+    )
+}
+
+/// An `Output` which pushes the final mix into a lock-free ring buffer read
+/// by a `CaptureReader`. Whole frames that do not fit are dropped.
+pub struct CaptureOutput {
+    ring: Arc<RingBuffer>,
+}
+
+/// The reading end of a `CaptureOutput`.
+pub struct CaptureReader {
+    ring: Arc<RingBuffer>,
+}
+
+struct RingBuffer {
+    data: Box<[AtomicU32]>,
+    write: AtomicUsize,
+    read: AtomicUsize,
+    sample_rate: AtomicUsize,
+    channels: AtomicUsize,
+    dropped: AtomicUsize,
+}
+
+impl Output for CaptureOutput {
+    fn init(&mut self, format: OutputFormat) {
+        self.ring
+            .sample_rate
+            .store(format.sample_rate as usize, Ordering::Release);
+        self.ring
+            .channels
+            .store(format.channels as usize, Ordering::Release);
+    }
+
+    fn mix(&mut self, samples: &[f32]) {
+        let ring = &*self.ring;
+        let capacity = ring.data.len();
+        let channels = cmp::max(ring.channels.load(Ordering::Acquire), 1);
+
+        let write = ring.write.load(Ordering::Relaxed);
+        let read = ring.read.load(Ordering::Acquire);
+        let free = capacity - write.wrapping_sub(read);
+
+        let count = cmp::min(samples.len(), free / channels * channels);
+        for (i, sample) in samples[..count].iter().enumerate() {
+            ring.data[write.wrapping_add(i) % capacity].store(sample.to_bits(), Ordering::Relaxed);
+        }
+        ring.write
+            .store(write.wrapping_add(count), Ordering::Release);
+
+        if count < samples.len() {
+            ring.dropped
+                .fetch_add(samples.len() - count, Ordering::Relaxed);
+        }
+    }
+}
+
+impl CaptureReader {
+    /// Copies as many captured samples as are available into `buffer` and
+    /// returns how many were written.
+    pub fn read(&mut self, buffer: &mut [f32]) -> usize {
+        let ring = &*self.ring;
+        let capacity = ring.data.len();
+
+        let read = ring.read.load(Ordering::Relaxed);
+        let write = ring.write.load(Ordering::Acquire);
+
+        let count = cmp::min(buffer.len(), write.wrapping_sub(read));
+        for (i, sample) in buffer[..count].iter_mut().enumerate() {
+            let bits = ring.data[read.wrapping_add(i) % capacity].load(Ordering::Relaxed);
+            *sample = f32::from_bits(bits);
+        }
+        ring.read.store(read.wrapping_add(count), Ordering::Release);
+
+        count
+    }
+
+    /// The number of samples waiting to be read.
+    pub fn available(&self) -> usize {
+        let read = self.ring.read.load(Ordering::Relaxed);
+        let write = self.ring.write.load(Ordering::Acquire);
+        write.wrapping_sub(read)
+    }
+
+    /// The mixer's sample rate, or 0 if the system has not initialized yet.
+    pub fn sample_rate(&self) -> i32 {
+        self.ring.sample_rate.load(Ordering::Acquire) as i32
+    }
+
+    /// The number of interleaved channels, or 0 if the system has not
+    /// initialized yet.
+    pub fn channels(&self) -> i32 {
+        self.ring.channels.load(Ordering::Acquire) as i32
+    }
+
+    /// The total number of samples dropped because the ring buffer was full.
+    pub fn dropped(&self) -> usize {
+        self.ring.dropped.load(Ordering::Relaxed)
+    }
+}
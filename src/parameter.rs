@@ -0,0 +1,128 @@
+use std::mem;
+use std::marker::PhantomData;
+
+use ffi::*;
+use error::*;
+use string::string_from_ptr;
+use system::System;
+
+/// A handle to a single parameter of an event instance. Setting values
+/// through this avoids looking the parameter up by name every time.
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct ParameterInstance<'sys> {
+    pub(crate) ptr: *mut FMOD_STUDIO_PARAMETERINSTANCE,
+    system: PhantomData<&'sys System>,
+}
+
+unsafe impl<'sys> Send for ParameterInstance<'sys> {}
+unsafe impl<'sys> Sync for ParameterInstance<'sys> {}
+
+impl<'sys> ParameterInstance<'sys> {
+    pub(crate) fn from_ptr(ptr: *mut FMOD_STUDIO_PARAMETERINSTANCE) -> Self {
+        ParameterInstance {
+            ptr,
+            system: PhantomData,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        unsafe { FMOD_Studio_ParameterInstance_IsValid(self.ptr) != 0 }
+    }
+
+    pub fn description(&self) -> Result<ParameterDescription> {
+        unsafe {
+            let mut desc = mem::zeroed();
+
+            FMOD_Studio_ParameterInstance_GetDescription(self.ptr, &mut desc).to_err()?;
+
+            Ok(ParameterDescription::from_raw(&desc))
+        }
+    }
+
+    pub fn get_value(&self) -> Result<f32> {
+        unsafe {
+            let mut value = 0.0;
+            FMOD_Studio_ParameterInstance_GetValue(self.ptr, &mut value).to_err()?;
+            Ok(value)
+        }
+    }
+
+    pub fn set_value(&self, value: f32) -> Result<()> {
+        unsafe { FMOD_Studio_ParameterInstance_SetValue(self.ptr, value).to_err() }
+    }
+
+    /// The raw FMOD handle.
+    ///
+    /// # Safety
+    ///
+    /// The returned pointer is only valid while the `System` this `ParameterInstance` was
+    /// retrieved from lives.
+    pub unsafe fn as_ptr(&self) -> *mut FMOD_STUDIO_PARAMETERINSTANCE {
+        self.ptr
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterDescription {
+    pub name: String,
+    pub index: i32,
+    pub minimum: f32,
+    pub maximum: f32,
+    pub default_value: f32,
+    pub parameter_type: ParameterType,
+}
+
+impl ParameterDescription {
+    pub(crate) unsafe fn from_raw(desc: &FMOD_STUDIO_PARAMETER_DESCRIPTION) -> Self {
+        ParameterDescription {
+            name: string_from_ptr(desc.name),
+            index: desc.index,
+            minimum: desc.minimum,
+            maximum: desc.maximum,
+            default_value: desc.defaultvalue,
+            parameter_type: ParameterType::from_raw(desc.type_),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ParameterType {
+    /// Controlled via the API using `EventInstance::set_parameter_value`.
+    GameControlled,
+    /// Distance between the event and the listener.
+    AutomaticDistance,
+    /// Angle between the event's forward vector and the vector pointing from
+    /// the event to the listener (0 to 180 degrees).
+    AutomaticEventConeAngle,
+    /// Horizontal angle between the event's forward vector and listener's
+    /// forward vector (-180 to 180 degrees).
+    AutomaticEventOrientation,
+    /// Horizontal angle between the listener's forward vector and the vector
+    /// pointing from the listener to the event (-180 to 180 degrees).
+    AutomaticDirection,
+    /// Angle between the listener's XZ plane and the vector pointing from
+    /// the listener to the event (-90 to 90 degrees).
+    AutomaticElevation,
+    /// Horizontal angle between the listener's forward vector and the global
+    /// positive Z axis (-180 to 180 degrees).
+    AutomaticListenerOrientation,
+}
+
+impl ParameterType {
+    pub(crate) fn from_raw(raw: FMOD_STUDIO_PARAMETER_TYPE) -> Self {
+        use ffi::FMOD_STUDIO_PARAMETER_TYPE::*;
+        use self::ParameterType::*;
+
+        match raw {
+            FMOD_STUDIO_PARAMETER_GAME_CONTROLLED => GameControlled,
+            FMOD_STUDIO_PARAMETER_AUTOMATIC_DISTANCE => AutomaticDistance,
+            FMOD_STUDIO_PARAMETER_AUTOMATIC_EVENT_CONE_ANGLE => AutomaticEventConeAngle,
+            FMOD_STUDIO_PARAMETER_AUTOMATIC_EVENT_ORIENTATION => AutomaticEventOrientation,
+            FMOD_STUDIO_PARAMETER_AUTOMATIC_DIRECTION => AutomaticDirection,
+            FMOD_STUDIO_PARAMETER_AUTOMATIC_ELEVATION => AutomaticElevation,
+            FMOD_STUDIO_PARAMETER_AUTOMATIC_LISTENER_ORIENTATION => AutomaticListenerOrientation,
+            _ => panic!(),
+        }
+    }
+}
@@ -0,0 +1,387 @@
+use std::{mem, ptr, slice};
+use std::ffi::CString;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::os::raw::{c_int, c_uint};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use ffi::*;
+use error::*;
+use event::{EventDescription, EventInstance};
+use system::{OutputType, System, SystemBuilder};
+
+/// Renders events faster than real time by running the mixer in one of the
+/// non-realtime output modes and stepping `System::update` manually. The
+/// final mix is captured from the master channel group.
+pub struct OfflineRenderer {
+    system: System,
+    dsp: *mut FMOD_DSP,
+    capture: Box<Mutex<Capture>>,
+    sample_rate: i32,
+}
+
+unsafe impl Send for OfflineRenderer {}
+
+struct Capture {
+    recording: bool,
+    channels: i32,
+    samples: Vec<f32>,
+}
+
+impl OfflineRenderer {
+    /// Creates the renderer's `System` from `builder`. Synchronous update is
+    /// always enabled, and the output is forced to `NoSoundNrt` unless the
+    /// builder already selected `WavWriterNrt`. Builders with a custom output
+    /// are rejected with `FmodError::InvalidParam`, since it would replace
+    /// the non-realtime output.
+    pub fn new(builder: SystemBuilder) -> Result<Self> {
+        if builder.custom_output.is_some() {
+            return Err(FmodError::InvalidParam);
+        }
+        let builder = match builder.output {
+            Some(OutputType::WavWriterNrt) => builder,
+            _ => builder.output(OutputType::NoSoundNrt),
+        };
+        let system = builder.synchronous_update(true).build()?;
+
+        unsafe {
+            let lowlevel = system.get_low_level_system()?;
+
+            let mut sample_rate = 0;
+            FMOD_System_GetSoftwareFormat(
+                lowlevel,
+                &mut sample_rate,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            ).to_err()?;
+
+            let capture = Box::new(Mutex::new(Capture {
+                recording: false,
+                channels: 0,
+                samples: Vec::new(),
+            }));
+
+            let mut desc: FMOD_DSP_DESCRIPTION = mem::zeroed();
+            desc.pluginsdkversion = FMOD_PLUGIN_SDK_VERSION;
+            for (dst, &src) in desc.name.iter_mut().zip(b"fmod-studio capture".iter()) {
+                *dst = src as _;
+            }
+            desc.numinputbuffers = 1;
+            desc.numoutputbuffers = 1;
+            desc.read = Some(capture_read);
+            desc.userdata = &*capture as *const Mutex<Capture> as *mut _;
+
+            let mut dsp = ptr::null_mut();
+            FMOD_System_CreateDSP(lowlevel, &desc, &mut dsp).to_err()?;
+
+            let mut master = ptr::null_mut();
+            let added = FMOD_System_GetMasterChannelGroup(lowlevel, &mut master)
+                .to_err()
+                .and_then(|_| {
+                    FMOD_ChannelGroup_AddDSP(
+                        master,
+                        FMOD_CHANNELCONTROL_DSP_INDEX::FMOD_CHANNELCONTROL_DSP_HEAD as c_int,
+                        dsp,
+                    ).to_err()
+                });
+            if let Err(err) = added {
+                FMOD_DSP_Release(dsp).to_err().ok();
+                return Err(err);
+            }
+
+            Ok(OfflineRenderer {
+                system,
+                dsp,
+                capture,
+                sample_rate,
+            })
+        }
+    }
+
+    /// The system used for rendering. Load banks through this before
+    /// rendering their events.
+    pub fn system(&self) -> &System {
+        &self.system
+    }
+
+    pub fn sample_rate(&self) -> i32 {
+        self.sample_rate
+    }
+
+    /// Creates an instance of `event`, plays it while applying the parameter
+    /// changes in `script`, and returns the mix for the length of the script.
+    /// The instance is stopped immediately and released afterwards.
+    pub fn render(
+        &self,
+        event: &EventDescription,
+        script: &RenderScript,
+    ) -> Result<RenderedAudio> {
+        let instance = event.create_owned_instance()?;
+        let result = self.render_instance(&instance, script);
+
+        drop(instance);
+        self.system.update()?;
+
+        result
+    }
+
+    /// Renders `length` of the mix as it is, without starting anything.
+    pub fn render_mix(&self, length: Duration) -> Result<RenderedAudio> {
+        self.record(length, |_| Ok(()))
+    }
+
+    fn render_instance(
+        &self,
+        instance: &EventInstance,
+        script: &RenderScript,
+    ) -> Result<RenderedAudio> {
+        let mut changes = script.changes.iter().peekable();
+        let mut apply = |time: Duration| -> Result<()> {
+            while let Some(change) = changes.peek().filter(|change| change.time <= time) {
+                unsafe {
+                    FMOD_Studio_EventInstance_SetParameterValue(
+                        instance.as_ptr(),
+                        change.name.as_ptr(),
+                        change.value,
+                    ).to_err()?;
+                }
+                changes.next();
+            }
+            Ok(())
+        };
+
+        apply(Duration::from_secs(0))?;
+        instance.start()?;
+
+        self.record(script.length, apply)
+    }
+
+    fn record<F>(&self, length: Duration, mut before_update: F) -> Result<RenderedAudio>
+    where
+        F: FnMut(Duration) -> Result<()>,
+    {
+        let frames = duration_to_frames(length, self.sample_rate);
+        {
+            let mut capture = self.capture.lock().unwrap();
+            capture.recording = true;
+            capture.samples.clear();
+        }
+
+        let result = (|| loop {
+            let rendered = {
+                let capture = self.capture.lock().unwrap();
+                if capture.channels > 0 {
+                    capture.samples.len() / capture.channels as usize
+                } else {
+                    0
+                }
+            };
+            if rendered >= frames {
+                return Ok(());
+            }
+
+            before_update(frames_to_duration(rendered, self.sample_rate))?;
+            self.system.update()?;
+        })();
+
+        let mut capture = self.capture.lock().unwrap();
+        capture.recording = false;
+        let samples = mem::take(&mut capture.samples);
+        result?;
+
+        let mut audio = RenderedAudio {
+            sample_rate: self.sample_rate,
+            channels: capture.channels,
+            samples,
+        };
+        audio.samples.truncate(frames * capture.channels as usize);
+
+        Ok(audio)
+    }
+}
+
+impl Drop for OfflineRenderer {
+    fn drop(&mut self) {
+        unsafe {
+            let mut master = ptr::null_mut();
+            if let Ok(lowlevel) = self.system.get_low_level_system() {
+                if FMOD_System_GetMasterChannelGroup(lowlevel, &mut master).to_err().is_ok() {
+                    FMOD_ChannelGroup_RemoveDSP(master, self.dsp).to_err().ok();
+                }
+            }
+            FMOD_DSP_Release(self.dsp)
+                .to_err()
+                .map_err(|err| {
+                    eprintln!(
+                        "Error dropping fmod_studio::OfflineRenderer: {:?}: {}",
+                        err,
+                        err.description()
+                    );
+                })
+                .ok();
+        }
+    }
+}
+
+unsafe extern "C" fn capture_read(
+    dsp_state: *mut FMOD_DSP_STATE,
+    inbuffer: *mut f32,
+    outbuffer: *mut f32,
+    length: c_uint,
+    inchannels: c_int,
+    outchannels: *mut c_int,
+) -> FMOD_RESULT {
+    let len = length as usize * inchannels as usize;
+    ptr::copy_nonoverlapping(inbuffer, outbuffer, len);
+    *outchannels = inchannels;
+
+    let mut userdata = ptr::null_mut();
+    let result = FMOD_DSP_GetUserData((*dsp_state).instance, &mut userdata);
+    if result != FMOD_RESULT::FMOD_OK || userdata.is_null() {
+        return FMOD_RESULT::FMOD_OK;
+    }
+
+    let capture = &*(userdata as *const Mutex<Capture>);
+    if let Ok(mut capture) = capture.lock() {
+        if capture.recording {
+            capture.channels = inchannels;
+            capture
+                .samples
+                .extend_from_slice(slice::from_raw_parts(inbuffer, len));
+        }
+    }
+
+    FMOD_RESULT::FMOD_OK
+}
+
+/// The length of a render and the parameter changes to make over its course.
+#[derive(Clone, Debug)]
+pub struct RenderScript {
+    length: Duration,
+    changes: Vec<ParameterChange>,
+}
+
+#[derive(Clone, Debug)]
+struct ParameterChange {
+    time: Duration,
+    name: CString,
+    value: f32,
+}
+
+impl RenderScript {
+    pub fn new(length: Duration) -> Self {
+        RenderScript {
+            length,
+            changes: Vec::new(),
+        }
+    }
+
+    /// Sets the parameter `name` to `value` once the render reaches `time`.
+    /// Changes are applied at the start of the first mixer block at or after
+    /// `time`.
+    pub fn set_parameter(mut self, time: Duration, name: &str, value: f32) -> Self {
+        let index = self.changes
+            .iter()
+            .position(|change| change.time > time)
+            .unwrap_or(self.changes.len());
+
+        self.changes.insert(
+            index,
+            ParameterChange {
+                time,
+                name: CString::new(name).unwrap(),
+                value,
+            },
+        );
+        self
+    }
+
+    pub fn length(&self) -> Duration {
+        self.length
+    }
+}
+
+/// Interleaved `f32` samples produced by an `OfflineRenderer`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderedAudio {
+    pub sample_rate: i32,
+    pub channels: i32,
+    pub samples: Vec<f32>,
+}
+
+impl RenderedAudio {
+    /// The number of sample frames, i.e. samples per channel.
+    pub fn frames(&self) -> usize {
+        if self.channels > 0 {
+            self.samples.len() / self.channels as usize
+        } else {
+            0
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        frames_to_duration(self.frames(), self.sample_rate)
+    }
+
+    /// Writes the samples as a 32-bit IEEE float WAV file.
+    pub fn write_wav<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let channels = self.channels as u16;
+        let data_len = (self.samples.len() * 4) as u32;
+        let block_align = channels * 4;
+        let byte_rate = self.sample_rate as u32 * block_align as u32;
+
+        w.write_all(b"RIFF")?;
+        w.write_all(&u32_le(36 + data_len))?;
+        w.write_all(b"WAVE")?;
+
+        w.write_all(b"fmt ")?;
+        w.write_all(&u32_le(16))?;
+        w.write_all(&u16_le(3))?; // WAVE_FORMAT_IEEE_FLOAT
+        w.write_all(&u16_le(channels))?;
+        w.write_all(&u32_le(self.sample_rate as u32))?;
+        w.write_all(&u32_le(byte_rate))?;
+        w.write_all(&u16_le(block_align))?;
+        w.write_all(&u16_le(32))?;
+
+        w.write_all(b"data")?;
+        w.write_all(&u32_le(data_len))?;
+        for sample in &self.samples {
+            w.write_all(&u32_le(sample.to_bits()))?;
+        }
+
+        w.flush()
+    }
+
+    pub fn write_wav_file(&self, path: &str) -> io::Result<()> {
+        self.write_wav(BufWriter::new(File::create(path)?))
+    }
+}
+
+fn u16_le(value: u16) -> [u8; 2] {
+    [value as u8, (value >> 8) as u8]
+}
+
+fn u32_le(value: u32) -> [u8; 4] {
+    [
+        value as u8,
+        (value >> 8) as u8,
+        (value >> 16) as u8,
+        (value >> 24) as u8,
+    ]
+}
+
+fn duration_to_frames(duration: Duration, sample_rate: i32) -> usize {
+    let rate = sample_rate as u64;
+    let frames = duration.as_secs() * rate + duration.subsec_nanos() as u64 * rate / 1_000_000_000;
+    frames as usize
+}
+
+fn frames_to_duration(frames: usize, sample_rate: i32) -> Duration {
+    let rate = sample_rate as u64;
+    let frames = frames as u64;
+    Duration::new(
+        frames / rate,
+        ((frames % rate) * 1_000_000_000 / rate) as u32,
+    )
+}
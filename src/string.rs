@@ -0,0 +1,32 @@
+use std::ptr;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+
+use ffi::*;
+use error::*;
+
+/// Reads a string from one of the FMOD functions taking a buffer, its size
+/// and a pointer receiving the size needed. `get` is called once to query
+/// the size and again to fill the buffer.
+pub(crate) fn get_string<F>(mut get: F) -> Result<String>
+where
+    F: FnMut(*mut c_char, c_int, *mut c_int) -> FMOD_RESULT,
+{
+    let mut size = 0;
+    get(ptr::null_mut(), 0, &mut size).to_err()?;
+
+    let mut data = vec![0u8; size as usize];
+    get(data.as_mut_ptr() as *mut c_char, size, ptr::null_mut()).to_err()?;
+    let len = data.iter().position(|&c| c == 0).unwrap_or(data.len());
+
+    Ok(String::from_utf8_lossy(&data[..len]).into_owned())
+}
+
+/// Copies a string owned by FMOD, treating null as empty.
+pub(crate) unsafe fn string_from_ptr(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(ptr).to_string_lossy().into_owned()
+    }
+}
@@ -1,15 +1,36 @@
 use std::{mem, ptr};
 use std::ffi::CString;
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
 
 use ffi::*;
 use error::*;
-use bank::Bank;
+use attributes::Attributes3D;
+use bank::{Bank, BankHandle, BankRegistry, LoadBankFlags};
+use bank_source::{system_callback, BankResources, BankSource, CustomBank};
+use bus::Bus;
 use event::EventDescription;
+use event_callback;
 use guid::Guid;
+use loading::{BankLoad, SampleDataLoad};
+use output::{Output, OutputMode, OutputPlugin, PendingOutput};
+use string::get_string;
+use vca::Vca;
 
+/// The Studio system. Banks, events, buses and VCAs retrieved from it borrow
+/// the system, so they cannot be used after it has been released.
 #[repr(C)]
 pub struct System {
     pub(crate) ptr: *mut FMOD_STUDIO_SYSTEM,
+    // Released only after the studio system has been released in `drop`
+    output: Option<Box<OutputPlugin>>,
+    // Banks shared through `BankHandle`, keyed by the file they were loaded from
+    banks: Arc<BankRegistry>,
+    // Woken by `update` so pending loads can check their progress
+    wakers: Mutex<Vec<Waker>>,
+    // Passed to FMOD as the system user data, so it is boxed and dropped
+    // only after the studio system has been released
+    resources: Box<BankResources>,
 }
 
 unsafe impl Send for System {}
@@ -17,58 +38,102 @@ unsafe impl Sync for System {}
 
 impl System {
     pub fn new(maxchannels: i32, liveupdate: bool) -> Result<Self> {
-        unsafe {
-            let mut ptr = ptr::null_mut();
+        SystemBuilder::new()
+            .max_channels(maxchannels)
+            .live_update(liveupdate)
+            .build()
+    }
 
-            let flags = if liveupdate {
-                FMOD_STUDIO_INIT_LIVEUPDATE
-            } else {
-                FMOD_STUDIO_INIT_NORMAL
-            };
+    pub fn is_valid(&self) -> bool {
+        unsafe { FMOD_Studio_System_IsValid(self.ptr) != 0 }
+    }
 
-            FMOD_Studio_System_Create(&mut ptr, FMOD_VERSION).to_err()?;
-            FMOD_Studio_System_Initialize(
-                ptr,
-                maxchannels,
-                flags,
-                FMOD_INIT_NORMAL,
-                ptr::null_mut(),
-            ).to_err()?;
+    /// Also wakes the futures of any pending asynchronous loads.
+    pub fn update(&self) -> Result<()> {
+        let result = unsafe { FMOD_Studio_System_Update(self.ptr).to_err() };
 
-            Ok(System { ptr })
+        let wakers = mem::take(&mut *self.wakers.lock().unwrap());
+        for waker in wakers {
+            waker.wake();
         }
-    }
 
-    pub fn update(&mut self) -> Result<()> {
-        unsafe { FMOD_Studio_System_Update(self.ptr).to_err() }
+        result
     }
 
-    pub fn flush_commands(&mut self) -> Result<()> {
+    pub fn flush_commands(&self) -> Result<()> {
         unsafe { FMOD_Studio_System_FlushCommands(self.ptr).to_err() }
     }
 
-    pub fn flush_sample_loading(&mut self) -> Result<()> {
+    pub fn flush_sample_loading(&self) -> Result<()> {
         unsafe { FMOD_Studio_System_FlushSampleLoading(self.ptr).to_err() }
     }
 
-    pub fn get_bank(&self, id: &str) -> Result<Bank> {
+    pub fn get_bank(&self, id: &str) -> Result<Bank<'_>> {
         unsafe {
             let mut ptr = ptr::null_mut();
             let cstr = CString::new(id).unwrap();
 
             FMOD_Studio_System_GetBank(self.ptr, cstr.as_ptr(), &mut ptr).to_err()?;
 
-            Ok(Bank { ptr })
+            Ok(Bank::from_ptr(ptr))
         }
     }
 
-    pub fn get_bank_by_id(&self, id: &Guid) -> Result<Bank> {
+    pub fn get_bank_by_id(&self, id: &Guid) -> Result<Bank<'_>> {
         unsafe {
             let mut ptr = ptr::null_mut();
 
             FMOD_Studio_System_GetBankByID(self.ptr, &id.inner, &mut ptr).to_err()?;
 
-            Ok(Bank { ptr })
+            Ok(Bank::from_ptr(ptr))
+        }
+    }
+
+    pub fn get_bank_count(&self) -> Result<i32> {
+        unsafe {
+            let mut count = 0;
+            FMOD_Studio_System_GetBankCount(self.ptr, &mut count).to_err()?;
+            Ok(count)
+        }
+    }
+
+    /// All currently loaded banks, including those still loading.
+    pub fn banks(&self) -> Result<Vec<Bank<'_>>> {
+        unsafe {
+            let mut count = 0;
+            FMOD_Studio_System_GetBankCount(self.ptr, &mut count).to_err()?;
+
+            let mut banks = Vec::with_capacity(count as usize);
+            FMOD_Studio_System_GetBankList(
+                self.ptr,
+                banks.as_mut_ptr() as *mut _,
+                banks.capacity() as i32,
+                &mut count,
+            ).to_err()?;
+            banks.set_len(count as usize);
+
+            Ok(banks)
+        }
+    }
+
+    pub fn get_bus(&self, id: &str) -> Result<Bus<'_>> {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            let cstr = CString::new(id).unwrap();
+
+            FMOD_Studio_System_GetBus(self.ptr, cstr.as_ptr(), &mut ptr).to_err()?;
+
+            Ok(Bus::from_ptr(ptr))
+        }
+    }
+
+    pub fn get_bus_by_id(&self, id: &Guid) -> Result<Bus<'_>> {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+
+            FMOD_Studio_System_GetBusByID(self.ptr, &id.inner, &mut ptr).to_err()?;
+
+            Ok(Bus::from_ptr(ptr))
         }
     }
 
@@ -82,51 +147,155 @@ impl System {
         }
     }
 
-    pub fn get_event(&self, id: &str) -> Result<EventDescription> {
+    pub fn get_event(&self, id: &str) -> Result<EventDescription<'_>> {
         unsafe {
             let mut ptr = ptr::null_mut();
             let cstr = CString::new(id).unwrap();
 
             FMOD_Studio_System_GetEvent(self.ptr, cstr.as_ptr(), &mut ptr).to_err()?;
 
-            Ok(EventDescription { ptr })
+            Ok(EventDescription::from_ptr(ptr))
         }
     }
 
-    pub fn get_event_by_id(&self, id: &Guid) -> Result<EventDescription> {
+    pub fn get_event_by_id(&self, id: &Guid) -> Result<EventDescription<'_>> {
         unsafe {
             let mut ptr = ptr::null_mut();
 
             FMOD_Studio_System_GetEventByID(self.ptr, &id.inner, &mut ptr).to_err()?;
 
-            Ok(EventDescription { ptr })
+            Ok(EventDescription::from_ptr(ptr))
         }
     }
 
-    pub fn load_bank_file(&mut self, file: &str, async: bool) -> Result<Bank> {
-        let flags = if async {
-            FMOD_STUDIO_LOAD_BANK_NONBLOCKING
-        } else {
-            FMOD_STUDIO_LOAD_BANK_NORMAL
-        };
+    pub fn get_num_listeners(&self) -> Result<i32> {
+        unsafe {
+            let mut count = 0;
+            FMOD_Studio_System_GetNumListeners(self.ptr, &mut count).to_err()?;
+            Ok(count)
+        }
+    }
+
+    /// Sets the number of listeners in the 3D sound scene, from 1 up to
+    /// `FMOD_MAX_LISTENERS`. `ListenerId`s above the new count become
+    /// invalid.
+    pub fn set_num_listeners(&self, count: i32) -> Result<()> {
+        unsafe { FMOD_Studio_System_SetNumListeners(self.ptr, count).to_err() }
+    }
+
+    /// Returns the id of listener `index`, or `FmodError::InvalidParam` if
+    /// there are not that many listeners.
+    pub fn listener(&self, index: i32) -> Result<ListenerId> {
+        if index < 0 || index >= self.get_num_listeners()? {
+            return Err(FmodError::InvalidParam);
+        }
+
+        Ok(ListenerId(index))
+    }
+
+    pub fn listeners(&self) -> Result<Vec<ListenerId>> {
+        Ok((0..self.get_num_listeners()?).map(ListenerId).collect())
+    }
+
+    pub fn get_listener_attributes(&self, listener: ListenerId) -> Result<Attributes3D> {
+        unsafe {
+            let mut attributes = mem::zeroed();
+            FMOD_Studio_System_GetListenerAttributes(self.ptr, listener.0, &mut attributes)
+                .to_err()?;
+            Ok(attributes.into())
+        }
+    }
+
+    pub fn set_listener_attributes(
+        &self,
+        listener: ListenerId,
+        attributes: &Attributes3D,
+    ) -> Result<()> {
+        unsafe {
+            let mut attributes = (*attributes).into();
+            FMOD_Studio_System_SetListenerAttributes(self.ptr, listener.0, &mut attributes).to_err()
+        }
+    }
+
+    pub fn get_listener_weight(&self, listener: ListenerId) -> Result<f32> {
+        unsafe {
+            let mut weight = 0.0;
+            FMOD_Studio_System_GetListenerWeight(self.ptr, listener.0, &mut weight).to_err()?;
+            Ok(weight)
+        }
+    }
 
+    /// Sets how much a listener contributes to the mix, from 0 to 1. Useful
+    /// for crossfading between listeners.
+    pub fn set_listener_weight(&self, listener: ListenerId, weight: f32) -> Result<()> {
+        unsafe { FMOD_Studio_System_SetListenerWeight(self.ptr, listener.0, weight).to_err() }
+    }
+
+    pub fn get_vca(&self, id: &str) -> Result<Vca<'_>> {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            let cstr = CString::new(id).unwrap();
+
+            FMOD_Studio_System_GetVCA(self.ptr, cstr.as_ptr(), &mut ptr).to_err()?;
+
+            Ok(Vca::from_ptr(ptr))
+        }
+    }
+
+    pub fn get_vca_by_id(&self, id: &Guid) -> Result<Vca<'_>> {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+
+            FMOD_Studio_System_GetVCAByID(self.ptr, &id.inner, &mut ptr).to_err()?;
+
+            Ok(Vca::from_ptr(ptr))
+        }
+    }
+
+    pub fn load_bank_file(&self, file: &str, flags: LoadBankFlags) -> Result<Bank<'_>> {
         unsafe {
             let mut ptr = ptr::null_mut();
             let cstr = CString::new(file).unwrap();
 
-            FMOD_Studio_System_LoadBankFile(self.ptr, cstr.as_ptr(), flags, &mut ptr).to_err()?;
+            FMOD_Studio_System_LoadBankFile(self.ptr, cstr.as_ptr(), flags.bits(), &mut ptr)
+                .to_err()?;
 
-            Ok(Bank { ptr })
+            Ok(Bank::from_ptr(ptr))
         }
     }
 
-    pub fn load_bank_memory(&mut self, buffer: &[u8], async: bool) -> Result<Bank> {
-        let flags = if async {
-            FMOD_STUDIO_LOAD_BANK_NONBLOCKING
-        } else {
-            FMOD_STUDIO_LOAD_BANK_NORMAL
-        };
+    /// Loads the bank at `file` and returns a handle which unloads it once
+    /// all of its clones are dropped. While a handle to a bank loaded from
+    /// the same `file` is alive, that bank is shared instead of loaded again,
+    /// unless it failed to load in the background. Sharing a bank loaded
+    /// with other `flags`, apart from `NONBLOCKING`, fails with
+    /// `FmodError::InvalidParam`.
+    pub fn load_bank_file_shared(
+        &self,
+        file: &str,
+        flags: LoadBankFlags,
+    ) -> Result<BankHandle<'_>> {
+        let shared = BankRegistry::share(&self.banks, file, flags, || {
+            self.load_bank_file(file, flags).map(|bank| bank.ptr)
+        })?;
+        Ok(BankHandle::new(shared))
+    }
 
+    /// Starts loading the bank at `file` in the background. The returned
+    /// future resolves once the bank has loaded. `NONBLOCKING` is always
+    /// added to `flags`.
+    pub fn load_bank_file_async(&self, file: &str, flags: LoadBankFlags) -> BankLoad<'_> {
+        let flags = flags | LoadBankFlags::NONBLOCKING;
+        BankLoad::new(self, self.load_bank_file(file, flags))
+    }
+
+    /// Starts loading the sample data of `bank` in the background. The
+    /// returned future resolves once it has loaded.
+    pub fn load_sample_data_async<'sys>(&'sys self, bank: &Bank<'sys>) -> SampleDataLoad<'sys> {
+        SampleDataLoad::new(self, *bank, bank.load_sample_data())
+    }
+
+    pub fn load_bank_memory(&self, buffer: &[u8], flags: LoadBankFlags) -> Result<Bank<'_>> {
         unsafe {
             let mut ptr = ptr::null_mut();
 
@@ -135,14 +304,75 @@ impl System {
                 buffer.as_ptr() as *const _,
                 buffer.len() as i32,
                 FMOD_STUDIO_LOAD_MEMORY_MODE::FMOD_STUDIO_LOAD_MEMORY,
-                flags,
+                flags.bits(),
                 &mut ptr,
             ).to_err()?;
 
-            Ok(Bank { ptr })
+            Ok(Bank::from_ptr(ptr))
         }
     }
 
+    /// Loads a bank directly from `buffer` without FMOD copying it. The data
+    /// must be aligned to `FMOD_STUDIO_LOAD_MEMORY_ALIGNMENT`, which
+    /// `AlignedBankBuffer` always is, and is kept until the bank has been
+    /// unloaded.
+    pub fn load_bank_memory_point<B>(&self, buffer: B, flags: LoadBankFlags) -> Result<Bank<'_>>
+    where
+        B: AsRef<[u8]> + Send + 'static,
+    {
+        // Boxed first so the data cannot move once its address is taken
+        let buffer = Box::new(buffer);
+        let (data, len) = {
+            let data = (*buffer).as_ref();
+            (data.as_ptr(), data.len())
+        };
+        if !(data as usize).is_multiple_of(FMOD_STUDIO_LOAD_MEMORY_ALIGNMENT as usize) {
+            return Err(FmodError::InvalidParam);
+        }
+
+        unsafe {
+            let mut ptr = ptr::null_mut();
+
+            FMOD_Studio_System_LoadBankMemory(
+                self.ptr,
+                data as *const _,
+                len as i32,
+                FMOD_STUDIO_LOAD_MEMORY_MODE::FMOD_STUDIO_LOAD_MEMORY_POINT,
+                flags.bits(),
+                &mut ptr,
+            ).to_err()?;
+            self.resources.insert(ptr, buffer);
+
+            Ok(Bank::from_ptr(ptr))
+        }
+    }
+
+    /// Loads a bank which FMOD reads from `source` as it would from a file,
+    /// without copying all of it into memory first. The source is kept until
+    /// the bank has been unloaded.
+    pub fn load_bank_custom<S>(&self, source: S, flags: LoadBankFlags) -> Result<Bank<'_>>
+    where
+        S: BankSource + 'static,
+    {
+        let custom = CustomBank::new(Box::new(source))?;
+        let info = custom.bank_info();
+
+        unsafe {
+            let mut ptr = ptr::null_mut();
+
+            FMOD_Studio_System_LoadBankCustom(self.ptr, &info, flags.bits(), &mut ptr).to_err()?;
+            self.resources.insert(ptr, custom);
+
+            Ok(Bank::from_ptr(ptr))
+        }
+    }
+
+    /// Unloads every bank, including those held by `BankHandle`s. The next
+    /// `load_bank_file_shared` of any file loads it again.
+    pub fn unload_all(&self) -> Result<()> {
+        self.banks.clear(|| unsafe { FMOD_Studio_System_UnloadAll(self.ptr).to_err() })
+    }
+
     pub fn lookup_id(&self, id: &str) -> Result<Guid> {
         unsafe {
             let mut guid = mem::uninitialized();
@@ -154,6 +384,35 @@ impl System {
         }
     }
 
+    /// The path of the event, bus, VCA, snapshot or bank with the GUID `id`.
+    /// Requires the master strings bank to be loaded.
+    pub fn lookup_path(&self, id: &Guid) -> Result<String> {
+        get_string(|path, size, retrieved| unsafe {
+            FMOD_Studio_System_LookupPath(self.ptr, &id.inner, path, size, retrieved)
+        })
+    }
+
+    pub(crate) fn register_waker(&self, waker: &Waker) {
+        let mut wakers = self.wakers.lock().unwrap();
+        if !wakers.iter().any(|registered| registered.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+    }
+
+    /// The low level system underneath the Studio system.
+    ///
+    /// # Safety
+    ///
+    /// The returned pointer is only valid while this `System` lives, and must
+    /// not be used to release the low level system.
+    pub unsafe fn get_low_level_system(&self) -> Result<*mut FMOD_SYSTEM> {
+        let mut lowlevel = ptr::null_mut();
+
+        FMOD_Studio_System_GetLowLevelSystem(self.ptr, &mut lowlevel).to_err()?;
+
+        Ok(lowlevel)
+    }
+
     pub unsafe fn as_ptr(&self) -> *mut FMOD_STUDIO_SYSTEM {
         self.ptr
     }
@@ -173,5 +432,381 @@ impl Drop for System {
                 })
                 .ok();
         }
+        event_callback::release_callbacks();
+    }
+}
+
+/// Identifies one of the listeners of a `System`. Obtained from
+/// `System::listener`, which checks the index against the current number of
+/// listeners.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ListenerId(i32);
+
+impl ListenerId {
+    pub fn index(&self) -> i32 {
+        self.0
+    }
+}
+
+/// Configures and creates a `System`. Settings which must be applied to the
+/// low level system before initialization (output type, DSP buffer size,
+/// software format, etc) are applied between creation and initialization.
+#[derive(Clone, Debug)]
+pub struct SystemBuilder {
+    max_channels: i32,
+    studio_flags: FMOD_STUDIO_INITFLAGS,
+    flags: FMOD_INITFLAGS,
+    pub(crate) output: Option<OutputType>,
+    output_file: Option<CString>,
+    pub(crate) custom_output: Option<PendingOutput>,
+    dsp_buffer_size: Option<(u32, i32)>,
+    software_format: Option<(i32, FMOD_SPEAKERMODE, i32)>,
+    software_channels: Option<i32>,
+    stream_buffer_size: Option<(u32, FMOD_TIMEUNIT)>,
+}
+
+impl SystemBuilder {
+    pub fn new() -> Self {
+        SystemBuilder {
+            max_channels: 512,
+            studio_flags: FMOD_STUDIO_INIT_NORMAL,
+            flags: FMOD_INIT_NORMAL,
+            output: None,
+            output_file: None,
+            custom_output: None,
+            dsp_buffer_size: None,
+            software_format: None,
+            software_channels: None,
+            stream_buffer_size: None,
+        }
+    }
+
+    /// The maximum number of channels to be used in FMOD. Defaults to 512.
+    pub fn max_channels(mut self, max_channels: i32) -> Self {
+        self.max_channels = max_channels;
+        self
+    }
+
+    /// Sets the raw `FMOD_STUDIO_INIT_*` flags, replacing any set so far.
+    pub fn studio_flags(mut self, flags: FMOD_STUDIO_INITFLAGS) -> Self {
+        self.studio_flags = flags;
+        self
+    }
+
+    /// Sets the raw low level `FMOD_INIT_*` flags, replacing any set so far.
+    /// `FMOD_INIT_THREAD_UNSAFE` is ignored, since it must not be used with
+    /// the Studio API.
+    pub fn flags(mut self, flags: FMOD_INITFLAGS) -> Self {
+        self.flags = flags & !FMOD_INIT_THREAD_UNSAFE;
+        self
+    }
+
+    /// Enable live update.
+    pub fn live_update(self, enable: bool) -> Self {
+        self.studio_flag(FMOD_STUDIO_INIT_LIVEUPDATE, enable)
+    }
+
+    /// Load banks even if they reference plugins that have not been loaded.
+    pub fn allow_missing_plugins(self, enable: bool) -> Self {
+        self.studio_flag(FMOD_STUDIO_INIT_ALLOW_MISSING_PLUGINS, enable)
+    }
+
+    /// Disable asynchronous processing and perform all processing on the
+    /// calling thread instead.
+    pub fn synchronous_update(self, enable: bool) -> Self {
+        self.studio_flag(FMOD_STUDIO_INIT_SYNCHRONOUS_UPDATE, enable)
+    }
+
+    /// Defer timeline callbacks until the main update.
+    pub fn deferred_callbacks(self, enable: bool) -> Self {
+        self.studio_flag(FMOD_STUDIO_INIT_DEFERRED_CALLBACKS, enable)
+    }
+
+    /// No additional threads are created for bank and resource loading.
+    /// Loading is driven from `System::update`.
+    pub fn load_from_update(self, enable: bool) -> Self {
+        self.studio_flag(FMOD_STUDIO_INIT_LOAD_FROM_UPDATE, enable)
+    }
+
+    /// No stream thread is created internally. Streams are driven from the
+    /// low level update.
+    pub fn stream_from_update(self, enable: bool) -> Self {
+        self.flag(FMOD_INIT_STREAM_FROM_UPDATE, enable)
+    }
+
+    /// No mixer thread is created internally. Mixing is driven from the low
+    /// level update.
+    pub fn mix_from_update(self, enable: bool) -> Self {
+        self.flag(FMOD_INIT_MIX_FROM_UPDATE, enable)
+    }
+
+    /// 3D calculations will be performed in right-handed coordinates.
+    pub fn right_handed_3d(self, enable: bool) -> Self {
+        self.flag(FMOD_INIT_3D_RIGHTHANDED, enable)
+    }
+
+    /// Enables usage of the channel lowpass filter.
+    pub fn channel_lowpass(self, enable: bool) -> Self {
+        self.flag(FMOD_INIT_CHANNEL_LOWPASS, enable)
+    }
+
+    /// All 3D based voices will add a software lowpass and highpass filter
+    /// effect into the DSP chain which will act as a distance-automated
+    /// bandpass filter.
+    pub fn channel_distance_filter(self, enable: bool) -> Self {
+        self.flag(FMOD_INIT_CHANNEL_DISTANCEFILTER, enable)
+    }
+
+    /// Enable TCP/IP based host which allows FMOD Studio or FMOD Profiler to
+    /// connect to it.
+    pub fn profile_enable(self, enable: bool) -> Self {
+        self.flag(FMOD_INIT_PROFILE_ENABLE, enable)
+    }
+
+    /// Any sounds that are 0 volume will go virtual and not be processed
+    /// except for having their positions updated virtually.
+    pub fn vol0_becomes_virtual(self, enable: bool) -> Self {
+        self.flag(FMOD_INIT_VOL0_BECOMES_VIRTUAL, enable)
+    }
+
+    /// With the geometry engine, only process the closest polygon rather
+    /// than accumulating all polygons the sound to listener line intersects.
+    pub fn geometry_use_closest(self, enable: bool) -> Self {
+        self.flag(FMOD_INIT_GEOMETRY_USECLOSEST, enable)
+    }
+
+    /// When using 5.1 output, prefer a Dolby Pro Logic II downmix for stereo.
+    pub fn prefer_dolby_downmix(self, enable: bool) -> Self {
+        self.flag(FMOD_INIT_PREFER_DOLBY_DOWNMIX, enable)
+    }
+
+    /// Slower, but adds level metering for every single DSP unit in the graph.
+    pub fn profile_meter_all(self, enable: bool) -> Self {
+        self.flag(FMOD_INIT_PROFILE_METER_ALL, enable)
+    }
+
+    /// Disables the SRS highpass filter which is otherwise applied to the LFE.
+    pub fn disable_srs_highpass_filter(self, enable: bool) -> Self {
+        self.flag(FMOD_INIT_DISABLE_SRS_HIGHPASSFILTER, enable)
+    }
+
+    /// Selects the output mode the low level system will use.
+    pub fn output(mut self, output: OutputType) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    /// The file the `WavWriter` and `WavWriterNrt` outputs write to. FMOD
+    /// writes to "fmodoutput.wav" in the working directory if this is not set.
+    pub fn output_file(mut self, file: &str) -> Self {
+        self.output_file = Some(CString::new(file).unwrap());
+        self
+    }
+
+    /// Registers `output` as an output plugin and sends the final mix to it,
+    /// replacing the output selected by `SystemBuilder::output`. Clones of
+    /// the builder share `output`, so only one system can be built with it;
+    /// building another fails with `FmodError::OutputAllocated`.
+    pub fn custom_output<O: Output>(mut self, output: O, mode: OutputMode) -> Self {
+        self.custom_output = Some(PendingOutput::new(Box::new(output), mode));
+        self
+    }
+
+    /// Sets the buffer size for the FMOD software mixing engine, in samples
+    /// per buffer and number of buffers.
+    pub fn dsp_buffer_size(mut self, buffer_length: u32, num_buffers: i32) -> Self {
+        self.dsp_buffer_size = Some((buffer_length, num_buffers));
+        self
+    }
+
+    /// Sets the output format for the software mixer.
+    pub fn software_format(
+        mut self,
+        sample_rate: i32,
+        speaker_mode: FMOD_SPEAKERMODE,
+        num_raw_speakers: i32,
+    ) -> Self {
+        self.software_format = Some((sample_rate, speaker_mode, num_raw_speakers));
+        self
+    }
+
+    /// Sets the maximum number of software mixed channels possible.
+    pub fn software_channels(mut self, num_software_channels: i32) -> Self {
+        self.software_channels = Some(num_software_channels);
+        self
+    }
+
+    /// Sets the internal buffer size for streams opened after this call,
+    /// measured in the given `FMOD_TIMEUNIT_*`.
+    pub fn stream_buffer_size(mut self, file_buffer_size: u32, unit: FMOD_TIMEUNIT) -> Self {
+        self.stream_buffer_size = Some((file_buffer_size, unit));
+        self
+    }
+
+    pub fn build(self) -> Result<System> {
+        let output = match self.custom_output {
+            Some(ref output) => Some(output.take()?),
+            None => None,
+        };
+
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            FMOD_Studio_System_Create(&mut ptr, FMOD_VERSION).to_err()?;
+
+            // Releases the studio system if any of the following steps fail
+            let mut system = System {
+                ptr,
+                output,
+                banks: BankRegistry::new(),
+                wakers: Mutex::new(Vec::new()),
+                resources: BankResources::new(),
+            };
+
+            let lowlevel = system.get_low_level_system()?;
+
+            if let Some(ref plugin) = system.output {
+                let mut handle = 0;
+                FMOD_System_RegisterOutput(lowlevel, plugin.description(), &mut handle).to_err()?;
+                FMOD_System_SetOutputByPlugin(lowlevel, handle).to_err()?;
+            } else if let Some(output) = self.output {
+                FMOD_System_SetOutput(lowlevel, output.to_raw()).to_err()?;
+            }
+            if let Some((length, count)) = self.dsp_buffer_size {
+                FMOD_System_SetDSPBufferSize(lowlevel, length, count).to_err()?;
+            }
+            if let Some((rate, mode, raw)) = self.software_format {
+                FMOD_System_SetSoftwareFormat(lowlevel, rate, mode, raw).to_err()?;
+            }
+            if let Some(channels) = self.software_channels {
+                FMOD_System_SetSoftwareChannels(lowlevel, channels).to_err()?;
+            }
+            if let Some((size, unit)) = self.stream_buffer_size {
+                FMOD_System_SetStreamBufferSize(lowlevel, size, unit).to_err()?;
+            }
+
+            // Custom outputs receive their plugin state through the extra
+            // driver data, the WAV writers their output file.
+            let extra_driver_data = match (system.output.as_mut(), self.output_file.as_ref()) {
+                (Some(plugin), _) => &mut **plugin as *mut OutputPlugin as *mut _,
+                (None, Some(file)) => file.as_ptr() as *mut _,
+                (None, None) => ptr::null_mut(),
+            };
+
+            let result = FMOD_Studio_System_Initialize(
+                ptr,
+                self.max_channels,
+                self.studio_flags,
+                self.flags,
+                extra_driver_data,
+            ).to_err();
+
+            // A failed initialize releases the studio system by itself
+            if let Err(err) = result {
+                let output = system.output.take();
+                mem::forget(system);
+                drop(output);
+                return Err(err);
+            }
+
+            let resources = &*system.resources as *const BankResources as *mut _;
+            FMOD_Studio_System_SetUserData(ptr, resources).to_err()?;
+            FMOD_Studio_System_SetCallback(
+                ptr,
+                Some(system_callback),
+                FMOD_STUDIO_SYSTEM_CALLBACK_BANK_UNLOAD,
+            ).to_err()?;
+
+            Ok(system)
+        }
+    }
+
+    fn studio_flag(mut self, flag: FMOD_STUDIO_INITFLAGS, enable: bool) -> Self {
+        if enable {
+            self.studio_flags |= flag;
+        } else {
+            self.studio_flags &= !flag;
+        }
+        self
+    }
+
+    fn flag(mut self, flag: FMOD_INITFLAGS, enable: bool) -> Self {
+        if enable {
+            self.flags |= flag;
+        } else {
+            self.flags &= !flag;
+        }
+        self
+    }
+}
+
+impl Default for SystemBuilder {
+    fn default() -> Self {
+        SystemBuilder::new()
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OutputType {
+    /// Picks the best output mode for the platform. This is the default.
+    #[default]
+    AutoDetect,
+    /// All calls in this mode succeed but make no sound.
+    NoSound,
+    /// Writes output to a .wav file, see `SystemBuilder::output_file`.
+    WavWriter,
+    /// Non-realtime version of `NoSound`. The mixer only runs when
+    /// `System::update` is called.
+    NoSoundNrt,
+    /// Non-realtime version of `WavWriter`. The mixer only runs when
+    /// `System::update` is called.
+    WavWriterNrt,
+    DSound,
+    WinMM,
+    Wasapi,
+    Asio,
+    PulseAudio,
+    Alsa,
+    CoreAudio,
+    XAudio,
+    Ps3,
+    AudioTrack,
+    OpenSL,
+    WiiU,
+    AudioOut,
+    Audio3D,
+    Atmos,
+    WebAudio,
+    NNAudio,
+}
+
+impl OutputType {
+    pub(crate) fn to_raw(self) -> FMOD_OUTPUTTYPE {
+        use ffi::FMOD_OUTPUTTYPE::*;
+        use self::OutputType::*;
+
+        match self {
+            AutoDetect => FMOD_OUTPUTTYPE_AUTODETECT,
+            NoSound => FMOD_OUTPUTTYPE_NOSOUND,
+            WavWriter => FMOD_OUTPUTTYPE_WAVWRITER,
+            NoSoundNrt => FMOD_OUTPUTTYPE_NOSOUND_NRT,
+            WavWriterNrt => FMOD_OUTPUTTYPE_WAVWRITER_NRT,
+            DSound => FMOD_OUTPUTTYPE_DSOUND,
+            WinMM => FMOD_OUTPUTTYPE_WINMM,
+            Wasapi => FMOD_OUTPUTTYPE_WASAPI,
+            Asio => FMOD_OUTPUTTYPE_ASIO,
+            PulseAudio => FMOD_OUTPUTTYPE_PULSEAUDIO,
+            Alsa => FMOD_OUTPUTTYPE_ALSA,
+            CoreAudio => FMOD_OUTPUTTYPE_COREAUDIO,
+            XAudio => FMOD_OUTPUTTYPE_XAUDIO,
+            Ps3 => FMOD_OUTPUTTYPE_PS3,
+            AudioTrack => FMOD_OUTPUTTYPE_AUDIOTRACK,
+            OpenSL => FMOD_OUTPUTTYPE_OPENSL,
+            WiiU => FMOD_OUTPUTTYPE_WIIU,
+            AudioOut => FMOD_OUTPUTTYPE_AUDIOOUT,
+            Audio3D => FMOD_OUTPUTTYPE_AUDIO3D,
+            Atmos => FMOD_OUTPUTTYPE_ATMOS,
+            WebAudio => FMOD_OUTPUTTYPE_WEBAUDIO,
+            NNAudio => FMOD_OUTPUTTYPE_NNAUDIO,
+        }
     }
 }
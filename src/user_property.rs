@@ -0,0 +1,90 @@
+use ffi::*;
+use error::*;
+use event::EventDescription;
+use string::string_from_ptr;
+
+/// A value attached to an event by the sound designer in FMOD Studio.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UserProperty {
+    Integer(i32),
+    Boolean(bool),
+    Float(f32),
+    String(String),
+}
+
+impl UserProperty {
+    /// Converts `prop` into its name and value.
+    pub(crate) unsafe fn from_raw(prop: &FMOD_STUDIO_USER_PROPERTY) -> (String, Self) {
+        use ffi::FMOD_STUDIO_USER_PROPERTY_TYPE::*;
+
+        let value = &prop.__bindgen_anon_1;
+        let property = match prop.type_ {
+            FMOD_STUDIO_USER_PROPERTY_TYPE_INTEGER => UserProperty::Integer(value.intvalue),
+            FMOD_STUDIO_USER_PROPERTY_TYPE_BOOLEAN => UserProperty::Boolean(value.boolvalue != 0),
+            FMOD_STUDIO_USER_PROPERTY_TYPE_FLOAT => UserProperty::Float(value.floatvalue),
+            FMOD_STUDIO_USER_PROPERTY_TYPE_STRING => {
+                UserProperty::String(string_from_ptr(value.stringvalue))
+            }
+            _ => panic!(),
+        };
+
+        (string_from_ptr(prop.name), property)
+    }
+
+    pub fn as_integer(&self) -> Option<i32> {
+        match *self {
+            UserProperty::Integer(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            UserProperty::Boolean(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f32> {
+        match *self {
+            UserProperty::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            UserProperty::String(ref value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Iterates over the names and values of all user properties of an event,
+/// created by `EventDescription::user_properties`.
+pub struct UserProperties<'sys> {
+    pub(crate) desc: EventDescription<'sys>,
+    pub(crate) index: i32,
+    pub(crate) count: i32,
+}
+
+impl<'sys> Iterator for UserProperties<'sys> {
+    type Item = Result<(String, UserProperty)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+
+        let property = self.desc.user_property_by_index(self.index);
+        self.index += 1;
+        Some(property)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.count - self.index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'sys> ExactSizeIterator for UserProperties<'sys> {}
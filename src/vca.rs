@@ -0,0 +1,89 @@
+use std::{fmt, mem, ptr};
+use std::marker::PhantomData;
+
+use ffi::*;
+use error::*;
+use guid::Guid;
+use string::get_string;
+use system::System;
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct Vca<'sys> {
+    pub(crate) ptr: *mut FMOD_STUDIO_VCA,
+    system: PhantomData<&'sys System>,
+}
+
+unsafe impl<'sys> Send for Vca<'sys> {}
+unsafe impl<'sys> Sync for Vca<'sys> {}
+
+impl<'sys> Vca<'sys> {
+    pub(crate) fn from_ptr(ptr: *mut FMOD_STUDIO_VCA) -> Self {
+        Vca {
+            ptr,
+            system: PhantomData,
+        }
+    }
+
+    /// Whether the bank containing the VCA is still loaded.
+    pub fn is_valid(&self) -> bool {
+        unsafe { FMOD_Studio_VCA_IsValid(self.ptr) != 0 }
+    }
+
+    pub fn get_id(&self) -> Result<Guid> {
+        unsafe {
+            let mut guid = mem::zeroed();
+
+            FMOD_Studio_VCA_GetID(self.ptr, &mut guid).to_err()?;
+
+            Ok(Guid { inner: guid })
+        }
+    }
+
+    pub fn get_path(&self) -> Result<String> {
+        get_string(|path, size, retrieved| unsafe {
+            FMOD_Studio_VCA_GetPath(self.ptr, path, size, retrieved)
+        })
+    }
+
+    /// The volume level set by the game.
+    pub fn get_volume(&self) -> Result<f32> {
+        unsafe {
+            let mut volume = 0.0;
+            FMOD_Studio_VCA_GetVolume(self.ptr, &mut volume, ptr::null_mut()).to_err()?;
+            Ok(volume)
+        }
+    }
+
+    /// The volume level after modulation and automation have been applied.
+    pub fn get_final_volume(&self) -> Result<f32> {
+        unsafe {
+            let mut volume = 0.0;
+            FMOD_Studio_VCA_GetVolume(self.ptr, ptr::null_mut(), &mut volume).to_err()?;
+            Ok(volume)
+        }
+    }
+
+    pub fn set_volume(&self, volume: f32) -> Result<()> {
+        unsafe { FMOD_Studio_VCA_SetVolume(self.ptr, volume).to_err() }
+    }
+
+    /// The raw FMOD handle.
+    ///
+    /// # Safety
+    ///
+    /// The returned pointer is only valid while the `System` this `Vca` was
+    /// retrieved from lives.
+    pub unsafe fn as_ptr(&self) -> *mut FMOD_STUDIO_VCA {
+        self.ptr
+    }
+}
+
+impl<'sys> fmt::Debug for Vca<'sys> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Vca")
+            .field("path", &self.get_path())
+            .field("volume", &self.get_volume())
+            .finish()
+    }
+}
@@ -0,0 +1,112 @@
+extern crate fmod_studio;
+
+use std::future::Future;
+use std::io::Cursor;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+use fmod_studio::{AlignedBankBuffer, FmodError, Guid, LoadBankFlags, OutputType, SystemBuilder};
+
+#[test]
+fn shared_bank_missing_file() {
+    let system = SystemBuilder::new()
+        .output(OutputType::NoSound)
+        .build()
+        .unwrap();
+
+    for _ in 0..2 {
+        let err = system
+            .load_bank_file_shared("missing.bank", LoadBankFlags::NORMAL)
+            .unwrap_err();
+        assert_eq!(err, FmodError::FileNotFound);
+    }
+}
+
+#[test]
+fn async_bank_missing_file() {
+    let system = SystemBuilder::new()
+        .output(OutputType::NoSound)
+        .build()
+        .unwrap();
+
+    let mut load = system.load_bank_file_async("missing.bank", LoadBankFlags::NORMAL);
+    let mut cx = Context::from_waker(Waker::noop());
+    let result = loop {
+        if let Poll::Ready(result) = Pin::new(&mut load).poll(&mut cx) {
+            break result;
+        }
+        system.update().unwrap();
+    };
+    assert_eq!(result.unwrap_err(), FmodError::FileNotFound);
+}
+
+#[test]
+fn custom_bank_invalid_data() {
+    let system = SystemBuilder::new()
+        .output(OutputType::NoSound)
+        .build()
+        .unwrap();
+
+    let source = Cursor::new(vec![0u8; 1024]);
+    let err = system.load_bank_custom(source, LoadBankFlags::NORMAL).unwrap_err();
+    assert_eq!(err, FmodError::Format);
+}
+
+struct Misaligned(AlignedBankBuffer);
+
+impl AsRef<[u8]> for Misaligned {
+    fn as_ref(&self) -> &[u8] {
+        &self.0[1..]
+    }
+}
+
+#[test]
+fn memory_point_bank() {
+    let system = SystemBuilder::new()
+        .output(OutputType::NoSound)
+        .build()
+        .unwrap();
+
+    let buffer = AlignedBankBuffer::from_slice(&[0u8; 1024]);
+    assert_eq!(buffer.as_ptr() as usize % 32, 0);
+
+    let err = system.load_bank_memory_point(buffer.clone(), LoadBankFlags::NORMAL).unwrap_err();
+    assert_eq!(err, FmodError::Format);
+
+    let err = system.load_bank_memory_point(Misaligned(buffer), LoadBankFlags::NORMAL).unwrap_err();
+    assert_eq!(err, FmodError::InvalidParam);
+}
+
+#[test]
+fn aligned_buffer_from_reader() {
+    let data: Vec<u8> = (0..20000).map(|i| i as u8).collect();
+    let buffer = AlignedBankBuffer::from_reader(Cursor::new(&data)).unwrap();
+    assert_eq!(buffer.as_ptr() as usize % 32, 0);
+    assert_eq!(&buffer[..], &data[..]);
+
+    let buffer = AlignedBankBuffer::from_reader(Cursor::new(Vec::new())).unwrap();
+    assert_eq!(buffer.len(), 0);
+}
+
+#[test]
+fn lookup_path_without_strings() {
+    let system = SystemBuilder::new()
+        .output(OutputType::NoSound)
+        .build()
+        .unwrap();
+
+    let err = system.lookup_path(&Guid::default()).unwrap_err();
+    assert_eq!(err, FmodError::EventNotFound);
+}
+
+#[test]
+fn unload_all() {
+    let system = SystemBuilder::new()
+        .output(OutputType::NoSound)
+        .build()
+        .unwrap();
+
+    assert_eq!(system.banks().unwrap().len(), 0);
+    system.unload_all().unwrap();
+    assert_eq!(system.get_bank_count().unwrap(), 0);
+}
@@ -1,6 +1,35 @@
 extern crate fmod_studio;
 
+use fmod_studio::{OutputType, SystemBuilder};
+
 #[test]
 fn basic_construction() {
-    fmod_studio::system::System::new(512, false).unwrap();
+    let system = SystemBuilder::new()
+        .max_channels(512)
+        .output(OutputType::NoSound)
+        .build()
+        .unwrap();
+    assert!(system.is_valid());
+}
+
+#[test]
+fn builder_construction() {
+    SystemBuilder::new()
+        .max_channels(64)
+        .synchronous_update(true)
+        .output(OutputType::NoSoundNrt)
+        .build()
+        .unwrap();
+}
+
+#[test]
+fn wav_writer_construction() {
+    let path = std::env::temp_dir().join("fmod_studio_wav_writer_construction.wav");
+
+    let system = SystemBuilder::new()
+        .output(OutputType::WavWriterNrt)
+        .output_file(path.to_str().unwrap())
+        .build()
+        .unwrap();
+    system.update().unwrap();
 }
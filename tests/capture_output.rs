@@ -0,0 +1,45 @@
+extern crate fmod_studio;
+
+use fmod_studio::{capture_output, FmodError, OutputMode, SystemBuilder};
+
+#[test]
+fn capture_non_realtime() {
+    let (output, mut reader) = capture_output(1 << 16);
+    let builder = SystemBuilder::new()
+        .synchronous_update(true)
+        .custom_output(output, OutputMode::NonRealtime);
+    let system = builder.clone().build().unwrap();
+
+    // The output cannot be fed by a second system
+    assert_eq!(builder.build().err(), Some(FmodError::OutputAllocated));
+
+    assert!(reader.sample_rate() > 0);
+    assert!(reader.channels() > 0);
+
+    for _ in 0..4 {
+        system.update().unwrap();
+    }
+
+    let available = reader.available();
+    assert!(available > 0);
+    assert_eq!(available % reader.channels() as usize, 0);
+
+    let mut samples = vec![1.0; available];
+    assert_eq!(reader.read(&mut samples), available);
+    assert!(samples.iter().all(|&sample| sample == 0.0));
+    assert_eq!(reader.available(), 0);
+}
+
+#[test]
+fn capture_realtime() {
+    let (output, reader) = capture_output(1 << 16);
+    let system = SystemBuilder::new()
+        .custom_output(output, OutputMode::Realtime)
+        .build()
+        .unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    system.update().unwrap();
+
+    assert!(reader.available() > 0);
+}
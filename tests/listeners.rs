@@ -0,0 +1,27 @@
+extern crate fmod_studio;
+
+use fmod_studio::{Attributes3D, FmodError, OutputType, SystemBuilder, Vector3};
+
+#[test]
+fn listener_management() {
+    let system = SystemBuilder::new()
+        .output(OutputType::NoSound)
+        .synchronous_update(true)
+        .build()
+        .unwrap();
+
+    assert_eq!(system.get_num_listeners().unwrap(), 1);
+    assert_eq!(system.listener(1), Err(FmodError::InvalidParam));
+
+    system.set_num_listeners(4).unwrap();
+    assert_eq!(system.listeners().unwrap().len(), 4);
+
+    let listener = system.listener(3).unwrap();
+    let attributes = Attributes3D::at(Vector3::new(1.0, 2.0, 3.0));
+    system.set_listener_attributes(listener, &attributes).unwrap();
+    system.set_listener_weight(listener, 0.25).unwrap();
+    system.update().unwrap();
+
+    assert_eq!(system.get_listener_attributes(listener).unwrap(), attributes);
+    assert_eq!(system.get_listener_weight(listener).unwrap(), 0.25);
+}
@@ -0,0 +1,25 @@
+extern crate fmod_studio;
+
+use std::time::Duration;
+
+use fmod_studio::{capture_output, FmodError, OfflineRenderer, OutputMode, SystemBuilder};
+
+#[test]
+fn render_silence() {
+    let renderer = OfflineRenderer::new(SystemBuilder::new()).unwrap();
+    let audio = renderer.render_mix(Duration::from_millis(500)).unwrap();
+
+    assert_eq!(audio.frames(), renderer.sample_rate() as usize / 2);
+    assert!(audio.samples.iter().all(|&sample| sample == 0.0));
+
+    let mut wav = Vec::new();
+    audio.write_wav(&mut wav).unwrap();
+    assert_eq!(wav.len(), 44 + audio.samples.len() * 4);
+}
+
+#[test]
+fn render_rejects_custom_output() {
+    let (output, _reader) = capture_output(1 << 16);
+    let builder = SystemBuilder::new().custom_output(output, OutputMode::Realtime);
+    assert_eq!(OfflineRenderer::new(builder).err(), Some(FmodError::InvalidParam));
+}